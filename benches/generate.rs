@@ -0,0 +1,43 @@
+//! How long `generate_maze` takes as mazes get bigger, it should grow with the
+//! number of cells. Fails if 2000x2000 takes a second or more.
+//!
+//! cargo bench --no-default-features --bench generate
+
+use std::time::{Duration, Instant};
+
+use rand::{rngs::StdRng, SeedableRng};
+use second_try::Generators::generate_maze;
+use second_try::Nodes::Grid;
+
+const RUNS: usize = 3;
+const LIMIT: Duration = Duration::from_secs(1);
+
+/// The fastest of a few runs, the slower ones are mostly noise.
+fn time(size: usize) -> Duration {
+    (0..RUNS)
+        .map(|run| {
+            let mut grid = Grid::new(size, size);
+            let mut rng = StdRng::seed_from_u64(run as u64);
+            let start = Instant::now();
+            generate_maze(size, size, &mut grid, &mut rng);
+            let elapsed = start.elapsed();
+            assert_eq!(grid.passages().count(), size * size - 1, "a {size}x{size} maze should be a spanning tree");
+            elapsed
+        })
+        .min()
+        .unwrap()
+}
+
+fn main() {
+    let mut largest = Duration::ZERO;
+    for size in [250, 500, 1000, 2000] {
+        let elapsed = time(size);
+        let per_cell = elapsed.as_nanos() as f64 / (size * size) as f64;
+        println!("{size}x{size}: {elapsed:?} ({per_cell:.1} ns per cell)");
+        largest = elapsed;
+    }
+    if largest >= LIMIT {
+        eprintln!("2000x2000 took {largest:?}, should be under {LIMIT:?}");
+        std::process::exit(1);
+    }
+}
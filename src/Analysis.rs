@@ -0,0 +1,159 @@
+//! Numbers that say how hard a maze is, so generators can be compared.
+
+use std::collections::VecDeque;
+use std::fmt;
+
+use crate::Filling::dead_ends;
+use crate::Nodes::{Direction, Grid, Traversal};
+use crate::Search::shortest_path;
+
+#[derive(PartialEq, Debug, Clone, Default)]
+pub struct MazeStats {
+    /// Cells on the shortest path, start and end included. `None` if there is no way through.
+    pub solution_length: Option<usize>,
+    pub dead_ends: usize,
+    /// How many ways on there are on average after walking into a junction, 0 without junctions.
+    pub branching_factor: f32,
+    /// The most cells in a row with exactly two passages, the longest stretch without a choice.
+    pub longest_corridor: usize,
+    /// Changes of direction along the shortest path.
+    pub solution_turns: usize,
+    /// Average steps from a cell off the shortest path back onto it.
+    pub average_distance_from_solution: f32,
+}
+
+/// Works the numbers out for the maze with this start and goal.
+pub fn analyse(grid: &Grid, start: usize, end: usize) -> MazeStats {
+    let solution = shortest_path(grid, start, end, Traversal::Undirected);
+
+    // every cell of a perfect maze averages out at one way on, so only junctions count
+    let junctions: Vec<u8> = (0..grid.len()).map(|index| grid.open_sides(index)).filter(|&sides| sides > 2).collect();
+    let branching_factor = if junctions.is_empty() {
+        0.0
+    } else {
+        junctions.iter().map(|&sides| (sides - 1) as f32).sum::<f32>() / junctions.len() as f32
+    };
+
+    let (solution_turns, average_distance_from_solution) = match &solution {
+        Some(path) => (turns(grid, path), distance_from(grid, path)),
+        None => (0, 0.0),
+    };
+
+    MazeStats {
+        solution_length: solution.as_ref().map(Vec::len),
+        dead_ends: dead_ends(grid).len(),
+        branching_factor,
+        longest_corridor: longest_corridor(grid),
+        solution_turns,
+        average_distance_from_solution,
+    }
+}
+
+fn turns(grid: &Grid, path: &[usize]) -> usize {
+    let directions: Vec<Option<Direction>> = path.windows(2).map(|step| grid.direction_to(step[0], step[1])).collect();
+    directions.windows(2).filter(|pair| pair[0] != pair[1]).count()
+}
+
+/// Walks every chain of two passage cells once.
+fn longest_corridor(grid: &Grid) -> usize {
+    let is_corridor = |index: usize| grid.open_sides(index) == 2;
+    let mut seen = vec![false; grid.len()];
+    let mut longest = 0;
+    for first in 0..grid.len() {
+        if seen[first] || !is_corridor(first) {
+            continue;
+        }
+        seen[first] = true;
+        let mut length = 1;
+        let mut stack = vec![first];
+        while let Some(current) = stack.pop() {
+            for direction in Direction::ALL {
+                if !grid.is_open(current, direction) {
+                    continue;
+                }
+                let next = grid.neighbour(current, direction).unwrap();
+                if !seen[next] && is_corridor(next) {
+                    seen[next] = true;
+                    length += 1;
+                    stack.push(next);
+                }
+            }
+        }
+        longest = longest.max(length);
+    }
+    longest
+}
+
+/// Breadth first search out from every cell of the path at once.
+fn distance_from(grid: &Grid, path: &[usize]) -> f32 {
+    let mut distances = vec![None; grid.len()];
+    let mut queue = VecDeque::new();
+    for &cell in path {
+        distances[cell] = Some(0);
+        queue.push_back(cell);
+    }
+    let (mut total, mut count) = (0, 0);
+    while let Some(current) = queue.pop_front() {
+        let distance = distances[current].unwrap() + 1;
+        for next in grid.node(current).neighbours(Traversal::Undirected) {
+            if distances[next.index].is_none() {
+                distances[next.index] = Some(distance);
+                total += distance;
+                count += 1;
+                queue.push_back(next.index);
+            }
+        }
+    }
+    if count == 0 {
+        return 0.0;
+    }
+    total as f32 / count as f32
+}
+
+impl fmt::Display for MazeStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.solution_length {
+            Some(length) => writeln!(f, "solution length: {length}")?,
+            None => writeln!(f, "solution length: no way through")?,
+        }
+        writeln!(f, "dead ends: {}", self.dead_ends)?;
+        writeln!(f, "branching factor: {:.2}", self.branching_factor)?;
+        writeln!(f, "longest corridor: {}", self.longest_corridor)?;
+        writeln!(f, "turns on the solution: {}", self.solution_turns)?;
+        write!(f, "average distance from the solution: {:.2}", self.average_distance_from_solution)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Ascii::from_ascii;
+
+    #[test]
+    fn counts_a_hand_built_maze() {
+        let maze = from_ascii(concat!(
+            "+--+--+--+--+\n",
+            "|     |     |\n",
+            "+  +  +  +  +\n",
+            "|  |  |G |  |\n",
+            "+--+  +--+  +\n",
+            "|S          |\n",
+            "+--+--+--+--+\n",
+        ))
+        .unwrap();
+        let stats = analyse(&maze.grid, maze.start, maze.goal);
+        assert_eq!(stats, MazeStats {
+            solution_length: Some(8),
+            // the start, the goal and the end of the branch on the left
+            dead_ends: 3,
+            // the only junction is next to the start, with two ways on
+            branching_factor: 2.0,
+            // along the bottom and round to the goal
+            longest_corridor: 5,
+            // right, up, left, down
+            solution_turns: 3,
+            // the branch is 1, 2, 3 and 4 steps off the path
+            average_distance_from_solution: 2.5,
+        });
+    }
+}
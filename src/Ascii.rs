@@ -0,0 +1,253 @@
+//! Mazes as plain text, for pasting into tickets and writing test mazes by hand.
+//!
+//! ```text
+//! +--+--+--+
+//! |G  .  . |
+//! +--+--+  +
+//! |S  .  . |
+//! +--+--+--+
+//! ```
+//!
+//! Every cell is two characters wide, `S` is the start, `G` the goal and `.`
+//! marks the solution. `SG` is a start that's also the goal. The top row of
+//! text is the top of the maze as the window draws it. Text can't say which way
+//! a passage goes, so one-way passages come back as ordinary ones, and gaps in
+//! the outer wall are ignored.
+
+use std::fmt;
+
+use crate::Nodes::{Direction, Grid};
+
+/// A maze read back from text.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AsciiMaze {
+    pub grid: Grid,
+    pub start: usize,
+    pub goal: usize,
+    /// Cells marked with `.`, in index order.
+    pub path: Vec<usize>,
+}
+
+/// Where the text stopped making sense, `line` and `column` start at 1.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}, column {}: {}", self.line, self.column, self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+fn error(line: usize, column: usize, message: impl Into<String>) -> ParseError {
+    ParseError {
+        line: line + 1,
+        column: column + 1,
+        message: message.into(),
+    }
+}
+
+pub fn to_ascii(grid: &Grid, start: usize, goal: usize, path: &[usize]) -> String {
+    let mut on_path = vec![false; grid.len()];
+    for &cell in path {
+        on_path[cell] = true;
+    }
+
+    let mut text = String::new();
+    for y in (0..grid.height).rev() {
+        // the wall above the row
+        text.push('+');
+        for x in 0..grid.width {
+            let open = grid.is_open(grid.index((x, y)), Direction::Up);
+            text.push_str(if open { "  +" } else { "--+" });
+        }
+        text.push('\n');
+
+        text.push('|');
+        for x in 0..grid.width {
+            let cell = grid.index((x, y));
+            text.push_str(match cell {
+                _ if cell == start && cell == goal => "SG",
+                _ if cell == start => "S ",
+                _ if cell == goal => "G ",
+                _ if on_path[cell] => ". ",
+                _ => "  ",
+            });
+            text.push(if grid.is_open(cell, Direction::Right) { ' ' } else { '|' });
+        }
+        text.push('\n');
+    }
+    text.push('+');
+    text.push_str(&"--+".repeat(grid.width));
+    text.push('\n');
+    text
+}
+
+pub fn from_ascii(text: &str) -> Result<AsciiMaze, ParseError> {
+    let mut lines: Vec<Vec<char>> = text.lines().map(|line| line.chars().collect()).collect();
+    while lines.last().is_some_and(|line| line.iter().all(|c| c.is_whitespace())) {
+        lines.pop();
+    }
+    let Some(first) = lines.first() else {
+        return Err(error(0, 0, "there's no maze"));
+    };
+    if first.len() < 4 || !(first.len() - 1).is_multiple_of(3) {
+        return Err(error(0, first.len(), "the top wall should be a `+` followed by `--+` for every cell"));
+    }
+    if lines.len() < 3 || lines.len().is_multiple_of(2) {
+        return Err(error(lines.len(), 0, "expected a wall line, then a cell line and a wall line for every row"));
+    }
+    let width = (first.len() - 1) / 3;
+    let height = lines.len() / 2;
+    let length = first.len();
+
+    let mut grid = Grid::new(width, height);
+    let mut start = None;
+    let mut goal = None;
+    let mut path = Vec::new();
+
+    for (line_number, line) in lines.iter().enumerate() {
+        if line.len() != length {
+            return Err(error(line_number, line.len().min(length), format!("expected {length} characters, found {}", line.len())));
+        }
+        // text goes top down, the grid bottom up
+        let row_above = height - line_number / 2;
+        if line_number.is_multiple_of(2) {
+            for x in 0..width {
+                let column = x * 3;
+                if line[column] != '+' {
+                    return Err(error(line_number, column, format!("expected `+`, found `{}`", line[column])));
+                }
+                let wall = match (line[column + 1], line[column + 2]) {
+                    ('-', '-') => true,
+                    (' ', ' ') => false,
+                    _ => return Err(error(line_number, column + 1, "a wall is either `--` or two spaces")),
+                };
+                let outside = line_number == 0 || row_above == 0;
+                if !wall && !outside {
+                    grid.connect((x, row_above), (x, row_above - 1));
+                }
+            }
+            if line[length - 1] != '+' {
+                return Err(error(line_number, length - 1, format!("expected `+`, found `{}`", line[length - 1])));
+            }
+            continue;
+        }
+
+        let y = row_above - 1;
+        for x in 0..width {
+            let column = x * 3;
+            let wall = match line[column] {
+                '|' => true,
+                ' ' => false,
+                other => return Err(error(line_number, column, format!("a wall is either `|` or a space, found `{other}`"))),
+            };
+            if !wall && x > 0 {
+                grid.connect((x, y), (x - 1, y));
+            }
+
+            let cell = grid.index((x, y));
+            let marker: String = line[column + 1..column + 3].iter().filter(|c| **c != ' ').collect();
+            let (is_start, is_goal) = match marker.as_str() {
+                "" => continue,
+                "S" => (true, false),
+                "G" => (false, true),
+                "SG" => (true, true),
+                "." => {
+                    path.push(cell);
+                    continue;
+                }
+                _ => return Err(error(line_number, column + 1, format!("a cell can hold `S`, `G`, `SG`, `.` or nothing, found `{marker}`"))),
+            };
+            for (marks, name, marked) in [(is_start, "S", &mut start), (is_goal, "G", &mut goal)] {
+                if !marks {
+                    continue;
+                }
+                if marked.is_some() {
+                    return Err(error(line_number, column + 1, format!("`{name}` shows up twice")));
+                }
+                *marked = Some(cell);
+            }
+        }
+        if !matches!(line[length - 1], '|' | ' ') {
+            return Err(error(line_number, length - 1, format!("a wall is either `|` or a space, found `{}`", line[length - 1])));
+        }
+    }
+
+    let last = lines.len() - 1;
+    let start = start.ok_or_else(|| error(last, 0, "the maze has no start, mark one with `S`"))?;
+    let goal = goal.ok_or_else(|| error(last, 0, "the maze has no goal, mark one with `G`"))?;
+    path.sort_unstable();
+    Ok(AsciiMaze {
+        grid,
+        start,
+        goal,
+        path,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Generators::GeneratorKind;
+    use crate::Nodes::Traversal;
+    use crate::Search::shortest_path;
+
+    #[test]
+    fn round_trips_a_generated_maze() {
+        let mut grid = Grid::new(10, 7);
+        GeneratorKind::Prim.generate(&mut grid, 3);
+        let (start, goal) = (grid.index((0, 0)), grid.index((9, 6)));
+        let solution = shortest_path(&grid, start, goal, Traversal::Undirected).unwrap();
+
+        let maze = from_ascii(&to_ascii(&grid, start, goal, &solution)).unwrap();
+        // text doesn't say which way a passage was carved, only that it's open
+        let walls = |grid: &Grid| (0..grid.len()).map(|cell| grid.walls(cell)).collect::<Vec<_>>();
+        assert_eq!(walls(&maze.grid), walls(&grid));
+        assert_eq!((maze.start, maze.goal), (start, goal));
+        // start and goal are drawn over their dots
+        let mut path: Vec<usize> = solution.into_iter().filter(|&cell| cell != start && cell != goal).collect();
+        path.sort_unstable();
+        assert_eq!(maze.path, path);
+    }
+
+    #[test]
+    fn round_trips_a_start_on_the_goal() {
+        let grid = Grid::new(1, 1);
+        let text = to_ascii(&grid, 0, 0, &[]);
+        assert_eq!(text, "+--+\n|SG|\n+--+\n");
+        let maze = from_ascii(&text).unwrap();
+        assert_eq!((maze.start, maze.goal), (0, 0));
+    }
+
+    #[test]
+    fn points_at_the_mistake() {
+        let wall = "+--+--+";
+        let cells = "|S  G |";
+        let cases = [
+            (String::new(), (1, 1), "no maze"),
+            ("+--".to_string(), (1, 4), "top wall"),
+            (format!("{wall}\n{cells}\n"), (3, 1), "wall line"),
+            (format!("{wall}\n|S  G\n{wall}"), (2, 6), "expected 7 characters"),
+            (format!("{wall}\n{cells}\n+--*--+"), (3, 4), "expected `+`"),
+            (format!("{wall}\n{cells}\n+--+- +"), (3, 5), "`--` or two spaces"),
+            (format!("{wall}\n{cells}\n+--+---"), (3, 7), "expected `+`"),
+            (format!("{wall}\n|S xG |\n{wall}"), (2, 4), "`|` or a space"),
+            (format!("{wall}\n|S  X |\n{wall}"), (2, 5), "found `X`"),
+            (format!("{wall}\n|S  S |\n{wall}"), (2, 5), "`S` shows up twice"),
+            (format!("{wall}\n|S  G x\n{wall}"), (2, 7), "`|` or a space"),
+            (format!("{wall}\n|   G |\n{wall}"), (3, 1), "no start"),
+            (format!("{wall}\n|S    |\n{wall}"), (3, 1), "no goal"),
+        ];
+        for (text, (line, column), message) in cases {
+            let error = from_ascii(&text).unwrap_err();
+            assert_eq!((error.line, error.column), (line, column), "{text:?} gave {error}");
+            assert!(error.message.contains(message), "{text:?} gave {error}");
+        }
+    }
+}
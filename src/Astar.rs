@@ -1,174 +1,494 @@
-use std::{borrow::Borrow, cell::RefCell, rc::Rc};
+use std::{cmp::Ordering, sync::Arc};
 
-use nannou::{lyon::algorithms::walk, prelude::*};
-use crate::Nodes::{Connection, Node};
-use std::collections::HashSet;
+use glam::Vec2;
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+use crate::Generators::simplify_name;
+use crate::Nodes::{Grid, Traversal};
+use crate::Filling::{DeadEndFiller, FillMode};
+use crate::Robots::{Hand, Pledge, Route, Tremaux, WallFollower};
+use crate::Search::{Bfs, Bidirectional, Dfs};
+use std::collections::BinaryHeap;
 
-#[derive(PartialEq)]
+#[derive(PartialEq, Debug, Clone, Copy)]
 pub enum Done {
     Found,
     NotFound(bool), // stuck, or not finished
 }
 
+/// How far we guess a node is from the end, used to order the open set.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum Heuristic {
+    Manhattan,
+    Euclidean,
+    Chebyshev,
+    Zero, // turns A* into dijkstra
+}
+
+impl Heuristic {
+    pub const ALL: [Heuristic; 4] = [Heuristic::Manhattan, Heuristic::Euclidean, Heuristic::Chebyshev, Heuristic::Zero];
+
+    pub fn estimate(&self, from: Vec2, to: Vec2) -> f32 {
+        let diff = (to - from).abs();
+        match self {
+            Heuristic::Manhattan => diff.x + diff.y,
+            Heuristic::Euclidean => diff.length(),
+            Heuristic::Chebyshev => diff.x.max(diff.y),
+            Heuristic::Zero => 0.0,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Heuristic::Manhattan => "manhattan",
+            Heuristic::Euclidean => "euclidean",
+            Heuristic::Chebyshev => "chebyshev",
+            Heuristic::Zero => "zero (dijkstra)",
+        }
+    }
+
+    /// Looks a heuristic up by name, "dijkstra" also gives `Zero`.
+    pub fn from_name(name: &str) -> Option<Heuristic> {
+        if simplify_name(name) == "dijkstra" {
+            return Some(Heuristic::Zero);
+        }
+        Heuristic::ALL.into_iter().find(|heuristic| heuristic.name().split(' ').next() == Some(simplify_name(name).as_str()))
+    }
+}
+
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum SolverKind {
+    AStar,
+    RandomStar,
+    Bfs,
+    Dfs,
+    Bidirectional,
+    LeftHand,
+    RightHand,
+    Pledge,
+    Tremaux,
+    DeadEndFilling,
+    CulDeSacFilling,
+}
+
+impl SolverKind {
+    pub const ALL: [SolverKind; 11] = [
+        SolverKind::AStar,
+        SolverKind::RandomStar,
+        SolverKind::Bfs,
+        SolverKind::Dfs,
+        SolverKind::Bidirectional,
+        SolverKind::LeftHand,
+        SolverKind::RightHand,
+        SolverKind::Pledge,
+        SolverKind::Tremaux,
+        SolverKind::DeadEndFilling,
+        SolverKind::CulDeSacFilling,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            SolverKind::AStar => "a*",
+            SolverKind::RandomStar => "random mouse",
+            SolverKind::Bfs => "breadth first",
+            SolverKind::Dfs => "depth first",
+            SolverKind::Bidirectional => "bidirectional",
+            SolverKind::LeftHand => "left hand",
+            SolverKind::RightHand => "right hand",
+            SolverKind::Pledge => "pledge",
+            SolverKind::Tremaux => "trémaux",
+            SolverKind::DeadEndFilling => "dead end filling",
+            SolverKind::CulDeSacFilling => "cul-de-sac filling",
+        }
+    }
+
+    /// Looks a solver up by name, ignoring case, spaces and dashes.
+    pub fn from_name(name: &str) -> Option<SolverKind> {
+        match simplify_name(name).as_str() {
+            "astar" => Some(SolverKind::AStar),
+            "random" | "randomstar" => Some(SolverKind::RandomStar),
+            "bfs" => Some(SolverKind::Bfs),
+            "dfs" => Some(SolverKind::Dfs),
+            "tremaux" => Some(SolverKind::Tremaux),
+            name => SolverKind::ALL.into_iter().find(|kind| simplify_name(kind.name()) == name),
+        }
+    }
+
+    /// `heuristic` is only used by A*.
+    pub fn build(&self, grid: Arc<Grid>, start: usize, end: usize, seed: u64, heuristic: Heuristic) -> Box<dyn MazeSolver> {
+        match self {
+            SolverKind::AStar => Box::new(AStar::with_heuristic(grid, start, end, heuristic)),
+            SolverKind::RandomStar => Box::new(RandomStar::new(grid, start, end, seed)),
+            SolverKind::Bfs => Box::new(Bfs::new(grid, start, end, seed)),
+            SolverKind::Dfs => Box::new(Dfs::new(grid, start, end, seed)),
+            SolverKind::Bidirectional => Box::new(Bidirectional::new(grid, start, end, seed)),
+            SolverKind::LeftHand => Box::new(WallFollower::with_hand(grid, start, end, Hand::Left)),
+            SolverKind::RightHand => Box::new(WallFollower::with_hand(grid, start, end, Hand::Right)),
+            SolverKind::Pledge => Box::new(Pledge::new(grid, start, end, seed)),
+            SolverKind::Tremaux => Box::new(Tremaux::new(grid, start, end, seed)),
+            SolverKind::DeadEndFilling => Box::new(DeadEndFiller::with_mode(grid, start, end, FillMode::DeadEnds)),
+            SolverKind::CulDeSacFilling => Box::new(DeadEndFiller::with_mode(grid, start, end, FillMode::CulDeSacs)),
+        }
+    }
+}
+
+/// An entry in the open set. the heap pops the lowest `g + h` first.
+pub struct OpenNode {
+    pub node: usize,
+    pub g: f32,
+    pub f: f32,
+}
+
+impl Ord for OpenNode {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // reversed so the BinaryHeap (a max heap) gives us the smallest f,
+        // on a tie prefer the node that got further from the start
+        other.f.total_cmp(&self.f).then(self.g.total_cmp(&other.g))
+    }
+}
+
+impl PartialOrd for OpenNode {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for OpenNode {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for OpenNode {}
+
 pub struct AStar{
-    pub start: Rc<Node>,
-    pub end: Rc<Node>,
-    pub path: Vec<Rc<Node>>,
-    pub walkers: Vec<Walker>,
+    pub grid: Arc<Grid>,
+    pub start: usize,
+    pub end: usize,
+    pub heuristic: Heuristic,
+    pub traversal: Traversal,
+    pub path: Vec<usize>, // the solution, filled in once the end is found
+    pub open: BinaryHeap<OpenNode>,
+    pub closed: Vec<usize>, // in the order they were expanded
+    pub is_done: Done,
+    closed_set: Vec<bool>,
+    came_from: Vec<Option<usize>>,
+    best_g: Vec<f32>,
 }
 
+/// A random mouse, wanders to a random neighbour every step until it bumps into the end.
 pub struct RandomStar{
-    pub start: Rc<Node>,
-    pub end: Rc<Node>,
-    pub path: Vec<Rc<Node>>,
-    pub walkers: Vec<Walker>,
+    pub grid: Arc<Grid>,
+    pub start: usize,
+    pub end: usize,
+    pub traversal: Traversal,
+    pub backtracking: bool, // allowed to go straight back where it came from
+    pub walker: Walker,
+    pub previous: Option<usize>,
+    pub moves: usize,
+    pub route: Route, // the walk with the loops cut out
+    pub path: Vec<usize>, // every cell it has been on, in the order it first got there
+    pub rng: StdRng,
+    seen: Vec<bool>,
 }
 
 pub struct Walker {
-    pub current_node: RefCell<Rc<Node>>,
-    pub path: Vec<Rc<Node>>,
+    pub current_node: usize,
+    pub path: Vec<usize>,
     pub is_done: Done,
 }
 
 impl Walker {
-    pub fn new(start: Rc<Node>, path: Vec<Rc<Node>>) -> Self{
+    pub fn new(start: usize, path: Vec<usize>) -> Self{
         Self {
-            current_node: RefCell::new(Rc::clone(&start)),
+            current_node: start,
             is_done: Done::NotFound(false),
             path,
         }
     }
 
-    pub fn step(&mut self, next_node: Rc<Node> ){
-        self.current_node = RefCell::new(Rc::clone(&next_node));
-        self.path.push(Rc::clone(&next_node))
+    pub fn step(&mut self, next_node: usize){
+        self.current_node = next_node;
+        self.path.push(next_node)
     }
 
 }
 
-impl MazeSolver for AStar {
-    fn new(start: Rc<Node>, end: Rc<Node>) -> AStar{
+impl AStar {
+    pub fn with_heuristic(grid: Arc<Grid>, start: usize, end: usize, heuristic: Heuristic) -> AStar {
+        let mut open = BinaryHeap::new();
+        let mut best_g = vec![f32::INFINITY; grid.len()];
+        best_g[start] = 0.0;
+        open.push(OpenNode {
+            f: heuristic.estimate(grid.position(start), grid.position(end)),
+            g: 0.0,
+            node: start,
+        });
+
         AStar {
-            start: Rc::clone(&start),
-            end: Rc::clone(&end),
-            walkers: vec![Walker::new(start, Vec::new())],
+            closed_set: vec![false; grid.len()],
+            came_from: vec![None; grid.len()],
+            grid,
+            start,
+            end,
+            heuristic,
+            traversal: Traversal::Undirected,
             path: Vec::new(),
+            open,
+            closed: Vec::new(),
+            is_done: Done::NotFound(false),
+            best_g,
         }
     }
 
+    /// Nodes waiting in the open set, without the stale entries of nodes we already expanded.
+    pub fn frontier(&self) -> impl Iterator<Item = usize> + '_ {
+        self.open.iter()
+            .map(|open| open.node)
+            .filter(|&node| !self.closed_set[node])
+    }
+
+}
+
+/// Follows parent pointers back from `end`, giving the path from the start to `end`.
+pub(crate) fn trace_back(came_from: &[Option<usize>], end: usize) -> Vec<usize> {
+    let mut current = end;
+    let mut path = vec![current];
+    while let Some(previous) = came_from[current] {
+        current = previous;
+        path.push(current);
+    }
+    path.reverse();
+    path
+}
+
+impl MazeSolver for AStar {
+    fn new(grid: Arc<Grid>, start: usize, end: usize, _seed: u64) -> AStar{
+        // nothing random about A*, the seed is only here to match the other solvers
+        AStar::with_heuristic(grid, start, end, Heuristic::Manhattan)
+    }
+
+    /// Expands a single node from the open set.
     fn step(&mut self){
-        let mut new_walkers: Vec<Walker> = Vec::new();
-        for walker in &mut self.walkers {
-            if let Done::Found = walker.is_done {
-                // continue;
-            }
-            if let Done::NotFound(x) = walker.is_done {
-                if x {
-                    continue;
+        if self.is_done != Done::NotFound(false) {
+            return;
+        }
+
+        let current = loop {
+            match self.open.pop() {
+                Some(open) if self.closed_set[open.node] => continue, // stale entry
+                Some(open) => break open,
+                None => {
+                    self.is_done = Done::NotFound(true);
+                    return;
                 }
             }
-            // walker should walk until it reaches a split
-            // it should keep walking but now clone itself for the split
-            let next_nodes: Vec<Connection>;
-            {
-                let current_node = walker.current_node.borrow();
-                next_nodes = Connection::all_out(current_node.connected_nodes.borrow());
-            }
-            if next_nodes.len() > 1 {
-                    for i in 1..next_nodes.len() {
-                        if self.path.contains(next_nodes[i].get_out().borrow()) {
-                            println!("stopped");
-                            walker.is_done = Done::NotFound(true);
-                            continue;
-                        }
-                        // println!("new_walker");
-                        new_walkers.push(Walker::new(Rc::clone(&walker.current_node.borrow()), walker.path.clone()));
-                        self.path.push(Rc::clone(next_nodes[i].get_out().borrow()));
-                        new_walkers.last_mut().unwrap().step(next_nodes[i].get_out());
-                    }
-            }
-            if walker.current_node.borrow().position == self.end.position {
-                println!("found end");
-                walker.is_done = Done::Found;
+        };
+
+        self.closed_set[current.node] = true;
+        self.closed.push(current.node);
+
+        if current.node == self.end {
+            self.path = trace_back(&self.came_from, self.end);
+            self.is_done = Done::Found;
+            return;
+        }
+
+        let next_nodes = self.grid.node(current.node).neighbours(self.traversal);
+        for next in next_nodes {
+            let next = next.index;
+            if self.closed_set[next] {
                 continue;
             }
-            if next_nodes.len() == 0 {
-                // println!("next_nodes: {:?}, current_node: {:?}", next_nodes, walker.current_node.borrow());
-                // println!("No More");
-                walker.is_done = Done::NotFound(true);
+            let g = current.g + 1.0;
+            if self.best_g[next] <= g {
                 continue;
             }
-            
-            walker.step(next_nodes[0].get_out());
-            self.path.push(Rc::clone(&walker.current_node.borrow()));
+            self.best_g[next] = g;
+            self.came_from[next] = Some(current.node);
+            self.open.push(OpenNode {
+                f: g + self.heuristic.estimate(self.grid.position(next), self.grid.position(self.end)),
+                g,
+                node: next,
+            });
         }
-        self.walkers.append(&mut new_walkers);
+    }
+
+    fn done(&self) -> Done {
+        self.is_done
+    }
+
+    fn solution(&self) -> Vec<usize> {
+        self.path.clone()
+    }
+
+    fn visited(&self) -> Vec<usize> {
+        self.closed.clone()
+    }
+
+    fn frontier(&self) -> Vec<usize> {
+        AStar::frontier(self).collect()
     }
 }
 
-impl MazeSolver for RandomStar {
-    fn new(start: Rc<Node>, end: Rc<Node>) -> RandomStar{
+impl RandomStar {
+    /// With `backtracking` off the mouse only turns back at dead ends.
+    pub fn with_backtracking(grid: Arc<Grid>, start: usize, end: usize, seed: u64, backtracking: bool) -> RandomStar {
+        let mut seen = vec![false; grid.len()];
+        seen[start] = true;
         RandomStar {
-            start: Rc::clone(&start),
-            end: Rc::clone(&end),
-            walkers: vec![Walker::new(start, Vec::new())],
-            path: Vec::new(),
-
+            route: Route::new(start, grid.len()),
+            grid,
+            start,
+            end,
+            traversal: Traversal::Undirected,
+            backtracking,
+            walker: Walker::new(start, Vec::new()),
+            previous: None,
+            moves: 0,
+            path: vec![start],
+            seen,
+            rng: StdRng::seed_from_u64(seed),
         }
     }
+}
+
+impl MazeSolver for RandomStar {
+    fn new(grid: Arc<Grid>, start: usize, end: usize, seed: u64) -> RandomStar{
+        RandomStar::with_backtracking(grid, start, end, seed, false)
+    }
 
+    /// Moves the mouse one cell.
     fn step(&mut self){
-        let mut new_walkers: Vec<Walker> = Vec::new();
-        for walker in &mut self.walkers {
-            if let Done::Found = walker.is_done {
-                // continue;
-            }
-            if let Done::NotFound(x) = walker.is_done {
-                if x {
-                    continue;
+        if self.walker.is_done != Done::NotFound(false) {
+            return;
+        }
+        let current = self.walker.current_node;
+        if current == self.end {
+            self.walker.is_done = Done::Found;
+            return;
+        }
+
+        let node = self.grid.node(current);
+        let forward: Vec<usize> = node.neighbours(self.traversal)
+            .iter()
+            .map(|next| next.index)
+            .filter(|&next| self.backtracking || Some(next) != self.previous)
+            .collect();
+        // at a dead end even a mouse that doesn't backtrack has to turn around
+        let next = match forward.choose(&mut self.rng) {
+            Some(&next) => next,
+            None => match node.get_random_neighbour(self.traversal, &mut self.rng) {
+                Some(next) => next.index,
+                None => {
+                    self.walker.is_done = Done::NotFound(true);
+                    return;
                 }
-            }
-            // walker should walk until it reaches a split
-            // it should keep walking but now clone itself for the split
-            let next_nodes: Vec<Connection>;
-            {
-                let current_node = walker.current_node.borrow();
-                next_nodes = Connection::all_out(current_node.connected_nodes.borrow());
-            }
-            if next_nodes.len() > 1 {
-                    for i in 1..next_nodes.len() {
-                        if self.path.contains(next_nodes[i].get_out().borrow()) {
-                            println!("stopped");
-                            walker.is_done = Done::NotFound(true);
-                            continue;
-                        }
-                        // println!("new_walker");
-                        new_walkers.push(Walker::new(Rc::clone(&walker.current_node.borrow()), walker.path.clone()));
-                        self.path.push(Rc::clone(next_nodes[i].get_out().borrow()));
-                        new_walkers.last_mut().unwrap().step(next_nodes[i].get_out());
-                    }
-            }
-            if walker.current_node.borrow().position == self.end.position {
-                println!("found end");
-                walker.is_done = Done::Found;
-                continue;
-            }
-            if next_nodes.len() == 0 {
-                // println!("next_nodes: {:?}, current_node: {:?}", next_nodes, walker.current_node.borrow());
-                // println!("No More");
-                walker.is_done = Done::NotFound(true);
-                continue;
-            }
-            
-            walker.step(next_nodes[0].get_out());
-            self.path.push(Rc::clone(&walker.current_node.borrow()));
+            },
+        };
+
+        // the walker's own path isn't kept, a mouse can walk for a very long time
+        self.previous = Some(current);
+        self.walker.current_node = next;
+        self.moves += 1;
+        self.route.push(next);
+        if !self.seen[next] {
+            self.seen[next] = true;
+            self.path.push(next);
+        }
+        if next == self.end {
+            self.walker.is_done = Done::Found;
+        }
+    }
+
+    fn done(&self) -> Done {
+        self.walker.is_done
+    }
+
+    fn solution(&self) -> Vec<usize> {
+        match self.walker.is_done {
+            Done::Found => self.route.cells.clone(),
+            Done::NotFound(_) => Vec::new(),
         }
-        self.walkers.append(&mut new_walkers);
     }
-} 
 
-pub trait MazeSolver {
+    fn visited(&self) -> Vec<usize> {
+        self.path.clone()
+    }
+
+    fn walkers(&self) -> &[Walker] {
+        std::slice::from_ref(&self.walker)
+    }
+
+    fn report(&self) -> String {
+        format!("{} moves", self.moves)
+    }
+}
+
+pub trait MazeSolver: Send {
     fn step(&mut self);
-    fn new(start: Rc<Node>, end: Rc<Node>) -> Self;
-    
-}
\ No newline at end of file
+    /// `start` and `end` are cell indices into `grid`. Anything random in the
+    /// solver is drawn from `seed`, so a run can be repeated.
+    fn new(grid: Arc<Grid>, start: usize, end: usize, seed: u64) -> Self where Self: Sized;
+    fn done(&self) -> Done;
+    /// The cells from start to end, empty until the end is found.
+    fn solution(&self) -> Vec<usize>;
+
+    // what the window draws while the solver is running
+
+    /// Cells the solver is finished with.
+    fn visited(&self) -> Vec<usize> {
+        Vec::new()
+    }
+    /// Cells the solver is about to look at.
+    fn frontier(&self) -> Vec<usize> {
+        Vec::new()
+    }
+    /// Cells ruled out, drawn grey.
+    fn filled(&self) -> Vec<usize> {
+        Vec::new()
+    }
+    /// Cells a search from the end is about to look at, for solvers that have one.
+    fn back_frontier(&self) -> Vec<usize> {
+        Vec::new()
+    }
+    fn walkers(&self) -> &[Walker] {
+        &[]
+    }
+    /// Anything else worth telling about the run, shown by the window and the cli.
+    fn report(&self) -> String {
+        String::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Generators::{braid, GeneratorKind};
+
+    fn solve(mut solver: impl MazeSolver) -> Vec<usize> {
+        while solver.done() == Done::NotFound(false) {
+            solver.step();
+        }
+        assert_eq!(solver.done(), Done::Found);
+        solver.solution()
+    }
+
+    #[test]
+    fn astar_matches_bfs_on_braided_mazes() {
+        for seed in 0..20 {
+            let mut grid = Grid::new(15, 11);
+            GeneratorKind::RecursiveBacktracker.generate(&mut grid, seed);
+            braid(&mut grid, 0.7, seed);
+            let grid = Arc::new(grid);
+            for end in [grid.len() - 1, grid.len() / 2] {
+                let shortest = solve(Bfs::new(Arc::clone(&grid), 0, end, seed)).len();
+                // every heuristic here is admissible, so none of them may cost a longer path
+                for heuristic in Heuristic::ALL {
+                    let path = solve(AStar::with_heuristic(Arc::clone(&grid), 0, end, heuristic));
+                    assert_eq!(path.len(), shortest, "seed {seed}, {heuristic:?}");
+                    assert!(path.windows(2).all(|pair| grid.direction_to(pair[0], pair[1]).is_some_and(|direction| grid.is_open(pair[0], direction))));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,191 @@
+//! Mazes as black and white PNG images, one pixel per cell and one per wall.
+//!
+//! A `width` by `height` maze is a `2 * width + 1` by `2 * height + 1` image.
+//! Cells sit on the odd pixels, the pixels between them are the walls and
+//! black means closed. Anything that isn't close to black counts as open, so a
+//! picture with the solution drawn in still loads. Like the window, the top of
+//! the image is the top of the maze.
+
+use std::{fmt, fs, io, path::Path};
+
+use crate::Nodes::{Direction, Grid};
+
+const WALL: [u8; 3] = [0, 0, 0];
+const OPEN: [u8; 3] = [255, 255, 255];
+const PATH: [u8; 3] = [0, 0, 255];
+
+/// A maze read from an image.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BitmapMaze {
+    pub grid: Grid,
+    /// Cells next to a gap in the outer wall, the usual way pictures mark the way in and out.
+    pub entrances: Vec<usize>,
+}
+
+#[derive(Debug)]
+pub enum BitmapError {
+    Io(io::Error),
+    Decode(png::DecodingError),
+    Encode(png::EncodingError),
+    Size(u32, u32), // width and height of an image that can't be a maze
+}
+
+impl fmt::Display for BitmapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BitmapError::Io(error) => write!(f, "file error: {error}"),
+            BitmapError::Decode(error) => write!(f, "bad png: {error}"),
+            BitmapError::Encode(error) => write!(f, "couldn't write the png: {error}"),
+            BitmapError::Size(width, height) => write!(f, "a {width}x{height} image isn't a maze, both sides have to be odd and at least 3"),
+        }
+    }
+}
+
+impl std::error::Error for BitmapError {}
+
+/// The image as rgb rows from the top, with `path` drawn in blue.
+fn pixels(grid: &Grid, path: &[usize]) -> (u32, u32, Vec<u8>) {
+    let width = 2 * grid.width + 1;
+    let height = 2 * grid.height + 1;
+    let mut pixels = vec![WALL; width * height];
+    let pixel = |cell: usize| {
+        let (x, y) = grid.coords(cell);
+        (2 * x + 1, 2 * (grid.height - 1 - y) + 1)
+    };
+    let at = |(x, y): (usize, usize)| y * width + x;
+
+    for cell in 0..grid.len() {
+        let (x, y) = pixel(cell);
+        pixels[at((x, y))] = OPEN;
+        // the right and down walls, the rest belong to the neighbours
+        if grid.is_open(cell, Direction::Right) {
+            pixels[at((x + 1, y))] = OPEN;
+        }
+        if grid.is_open(cell, Direction::Down) {
+            pixels[at((x, y + 1))] = OPEN;
+        }
+    }
+    for (index, &cell) in path.iter().enumerate() {
+        let (x, y) = pixel(cell);
+        pixels[at((x, y))] = PATH;
+        if let Some(&next) = path.get(index + 1) {
+            let (next_x, next_y) = pixel(next);
+            pixels[at(((x + next_x) / 2, (y + next_y) / 2))] = PATH;
+        }
+    }
+    (width as u32, height as u32, pixels.concat())
+}
+
+/// Encodes the maze as an rgb png.
+pub fn to_png(grid: &Grid, path: &[usize]) -> Result<Vec<u8>, BitmapError> {
+    let (width, height, data) = pixels(grid, path);
+    let mut bytes = Vec::new();
+    let mut encoder = png::Encoder::new(&mut bytes, width, height);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().map_err(BitmapError::Encode)?;
+    writer.write_image_data(&data).map_err(BitmapError::Encode)?;
+    writer.finish().map_err(BitmapError::Encode)?;
+    Ok(bytes)
+}
+
+pub fn from_png(bytes: &[u8]) -> Result<BitmapMaze, BitmapError> {
+    let mut decoder = png::Decoder::new(bytes);
+    // palettes, low bit depths and 16 bit channels all come out as 8 bit gray or color
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info().map_err(BitmapError::Decode)?;
+    let mut data = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut data).map_err(BitmapError::Decode)?;
+    let (image_width, image_height) = (info.width, info.height);
+    if image_width < 3 || image_height < 3 || image_width.is_multiple_of(2) || image_height.is_multiple_of(2) {
+        return Err(BitmapError::Size(image_width, image_height));
+    }
+
+    let channels = info.color_type.samples();
+    let is_wall = |x: usize, y: usize| {
+        let start = y * info.line_size + x * channels;
+        let pixel = &data[start..start + channels];
+        let (color, alpha) = match channels {
+            1 => (&pixel[..1], 255),
+            2 => (&pixel[..1], pixel[1]),
+            3 => (pixel, 255),
+            _ => (&pixel[..3], pixel[3]),
+        };
+        // see-through counts as open, that's usually the background
+        alpha >= 128 && color.iter().all(|&channel| channel < 128)
+    };
+
+    let width = (image_width as usize - 1) / 2;
+    let height = (image_height as usize - 1) / 2;
+    let mut grid = Grid::new(width, height);
+    let mut entrances = Vec::new();
+    for row in 0..height {
+        let y = height - 1 - row;
+        let pixel_y = 2 * row + 1;
+        for x in 0..width {
+            let pixel_x = 2 * x + 1;
+            if x + 1 < width && !is_wall(pixel_x + 1, pixel_y) {
+                grid.connect((x + 1, y), (x, y));
+            }
+            if y > 0 && !is_wall(pixel_x, pixel_y + 1) {
+                grid.connect((x, y - 1), (x, y));
+            }
+
+            let on_edge = [
+                (x == 0, (pixel_x - 1, pixel_y)),
+                (x + 1 == width, (pixel_x + 1, pixel_y)),
+                (row == 0, (pixel_x, pixel_y - 1)),
+                (row + 1 == height, (pixel_x, pixel_y + 1)),
+            ];
+            if on_edge.iter().any(|&(edge, (wall_x, wall_y))| edge && !is_wall(wall_x, wall_y)) {
+                entrances.push(grid.index((x, y)));
+            }
+        }
+    }
+    Ok(BitmapMaze { grid, entrances })
+}
+
+pub fn save_png(file: impl AsRef<Path>, grid: &Grid, path: &[usize]) -> Result<(), BitmapError> {
+    fs::write(file, to_png(grid, path)?).map_err(BitmapError::Io)
+}
+
+pub fn load_png(file: impl AsRef<Path>) -> Result<BitmapMaze, BitmapError> {
+    from_png(&fs::read(file).map_err(BitmapError::Io)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Generators::GeneratorKind;
+    use crate::Nodes::Traversal;
+    use crate::Search::shortest_path;
+
+    // pixels only know open or closed, not which way a passage was carved
+    fn walls(grid: &Grid) -> Vec<u8> {
+        (0..grid.len()).map(|cell| grid.walls(cell)).collect()
+    }
+
+    #[test]
+    fn round_trips_png() {
+        let mut grid = Grid::new(9, 5);
+        GeneratorKind::Wilson.generate(&mut grid, 8);
+        let path = shortest_path(&grid, 0, grid.len() - 1, Traversal::Undirected).unwrap();
+
+        // the blue path shouldn't read as walls
+        for drawn in [&path[..], &[]] {
+            let maze = from_png(&to_png(&grid, drawn).unwrap()).unwrap();
+            assert_eq!(walls(&maze.grid), walls(&grid));
+            assert!(maze.entrances.is_empty());
+        }
+    }
+
+    #[test]
+    fn rejects_even_sizes() {
+        let mut bytes = Vec::new();
+        let mut encoder = png::Encoder::new(&mut bytes, 4, 3);
+        encoder.set_color(png::ColorType::Grayscale);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder.write_header().unwrap().write_image_data(&[0; 12]).unwrap();
+        assert!(matches!(from_png(&bytes), Err(BitmapError::Size(4, 3))));
+    }
+}
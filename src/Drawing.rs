@@ -0,0 +1,84 @@
+use nannou::prelude::*;
+use second_try::Nodes::{Connection, Direction, Node};
+
+/// Drawing lives with the viewer so the library doesn't need nannou.
+pub trait DrawNode {
+    fn draw(&self, draw: &Draw, color: Srgba<u8>);
+    #[allow(dead_code)] // only used when debugging connection directions, see `view`
+    fn draw_connection(&self, draw: &Draw);
+}
+
+impl DrawNode for Node<'_> {
+    fn draw(&self, draw: &Draw, color: Srgba<u8>) {
+        
+        let wall_length = Node::DIST; // The length of each wall
+
+        // Draw the node itself (optional, e.g., as a small circle)
+        draw.ellipse()
+            .x_y(self.position.x * Node::DIST, self.position.y * Node::DIST)
+            .radius(Node::RAD)
+            .color(color);
+        
+        // Draw walls on every side without a passage
+        for direction in Direction::ALL {
+            if !self.grid.is_open(self.index, direction) {
+                // Draw the wall
+                match direction {
+                    Direction::Up => {
+                        draw.line()
+                            .start(self.position * Node::DIST + vec2(-wall_length / 2.0, wall_length / 2.0))
+                            .end(self.position * Node::DIST + vec2(wall_length / 2.0, wall_length / 2.0))
+                            .color(BLACK);
+                    },
+                    Direction::Down => {
+                        draw.line()
+                            .start(self.position * Node::DIST + vec2(-wall_length / 2.0, -wall_length / 2.0))
+                            .end(self.position * Node::DIST + vec2(wall_length / 2.0, -wall_length / 2.0))
+                            .color(BLACK);
+                    },
+                    Direction::Right => {
+                        draw.line()
+                            .start(self.position * Node::DIST + vec2(wall_length / 2.0, -wall_length / 2.0))
+                            .end(self.position * Node::DIST + vec2(wall_length / 2.0, wall_length / 2.0))
+                            .color(BLACK);
+                    },
+                    Direction::Left => {
+                        draw.line()
+                            .start(self.position * Node::DIST + vec2(-wall_length / 2.0, -wall_length / 2.0))
+                            .end(self.position * Node::DIST + vec2(-wall_length / 2.0, wall_length / 2.0))
+                            .color(BLACK);
+                    },
+                }
+            } else if self.grid.one_way_passages(self.index) & self.grid.out_passages(self.index) & direction.bit() != 0 {
+                // one-way passages get a little arrow pointing the way you're allowed to go
+                let (dx, dy) = direction.offset();
+                let offset = vec2(dx as f32, dy as f32) * wall_length / 2.0;
+                draw.arrow()
+                    .start(self.position * Node::DIST)
+                    .end(self.position * Node::DIST + offset)
+                    .head_length(4.0)
+                    .head_width(3.0)
+                    .color(BLACK);
+            }
+        }
+    }
+
+    fn draw_connection(&self, draw: &Draw) {
+        // if self.position == vec2(-5.0, -5.0) {
+        //     println!("drawing connection for {:?}", self.connected_nodes());
+        // }
+        self.connected_nodes().iter().for_each(|node| {
+            match node {
+                Connection::In(vec) => draw.arrow()
+                    .start(vec.position * Node::DIST)
+                    .end(self.position * Node::DIST)
+                    .color(BLACK),
+                Connection::Out(vec) => draw.arrow()
+                        .start(self.position * Node::DIST)
+                        .end(vec.position * Node::DIST)
+                        .color(BLACK),
+            };
+        });
+        
+    }
+}
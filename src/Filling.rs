@@ -0,0 +1,245 @@
+//! Solvers that look at the whole maze at once and fill in everything that
+//! can't be on the way, until only the solution is left.
+//!
+//! Filling goes by the open walls, a dead end is no use whichever way its
+//! passage goes. The walk through what's left at the end keeps to one-way
+//! passages.
+
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+use crate::Astar::{trace_back, Done, MazeSolver};
+use crate::Nodes::{Direction, Grid, Traversal};
+
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum FillMode {
+    /// Fill dead ends until none are left, enough for mazes without loops.
+    DeadEnds,
+    /// Also fill loops that only have one way in, for braided mazes.
+    CulDeSacs,
+}
+
+/// Cells with a single passage.
+pub fn dead_ends(grid: &Grid) -> Vec<usize> {
+    (0..grid.len()).filter(|&index| grid.open_sides(index) == 1).collect()
+}
+
+/// Fills one cell per step, `filled` ends up as every cell off the solution.
+pub struct DeadEndFiller {
+    pub grid: Arc<Grid>,
+    pub start: usize,
+    pub end: usize,
+    pub mode: FillMode,
+    pub traversal: Traversal,
+    pub path: Vec<usize>,
+    pub filled: Vec<bool>,
+    pub fill_order: Vec<usize>, // the filled cells, in the order they were filled
+    pub is_done: Done,
+    queue: VecDeque<usize>,
+    open: Vec<u8>, // passages to cells that aren't filled yet
+}
+
+impl DeadEndFiller {
+    pub fn with_mode(grid: Arc<Grid>, start: usize, end: usize, mode: FillMode) -> DeadEndFiller {
+        let open: Vec<u8> = (0..grid.len()).map(|index| grid.open_sides(index)).collect();
+        // cells with no passages at all are as good as dead ends
+        let queue = (0..grid.len())
+            .filter(|&index| open[index] <= 1 && index != start && index != end)
+            .collect();
+        DeadEndFiller {
+            filled: vec![false; grid.len()],
+            grid,
+            start,
+            end,
+            mode,
+            traversal: Traversal::Undirected,
+            path: Vec::new(),
+            fill_order: Vec::new(),
+            is_done: Done::NotFound(false),
+            queue,
+            open,
+        }
+    }
+
+    fn fill(&mut self, index: usize) {
+        if self.filled[index] {
+            return;
+        }
+        self.filled[index] = true;
+        self.fill_order.push(index);
+        for direction in Direction::ALL {
+            if !self.grid.is_open(index, direction) {
+                continue;
+            }
+            let next = self.grid.neighbour(index, direction).unwrap();
+            if self.filled[next] {
+                continue;
+            }
+            self.open[next] -= 1;
+            if self.open[next] <= 1 && next != self.start && next != self.end {
+                self.queue.push_back(next);
+            }
+        }
+    }
+
+    /// Queues every part of the maze that hangs off a single passage (a bridge)
+    /// and doesn't hold the end, plus anything we can't get to from the start.
+    /// Returns false when there was nothing left to fill.
+    fn find_cul_de_sacs(&mut self) -> bool {
+        let grid = Arc::clone(&self.grid);
+        let unset = usize::MAX;
+        let mut discovered = vec![unset; grid.len()];
+        let mut low = vec![unset; grid.len()];
+        let mut parent = vec![None; grid.len()];
+        let mut order = Vec::new(); // cells by discovery, a subtree is a run in here
+        let mut stack = vec![(self.start, 0)];
+        discovered[self.start] = 0;
+        low[self.start] = 0;
+        order.push(self.start);
+
+        let mut found = Vec::new();
+        while let Some(&mut (cell, ref mut next_side)) = stack.last_mut() {
+            if *next_side < Direction::ALL.len() {
+                let direction = Direction::ALL[*next_side];
+                *next_side += 1;
+                if !grid.is_open(cell, direction) {
+                    continue;
+                }
+                let next = grid.neighbour(cell, direction).unwrap();
+                if self.filled[next] {
+                    continue;
+                }
+                if discovered[next] == unset {
+                    discovered[next] = order.len();
+                    low[next] = order.len();
+                    parent[next] = Some(cell);
+                    order.push(next);
+                    stack.push((next, 0));
+                } else if parent[cell] != Some(next) {
+                    low[cell] = low[cell].min(discovered[next]);
+                }
+                continue;
+            }
+
+            stack.pop();
+            let Some(above) = parent[cell] else {
+                continue;
+            };
+            low[above] = low[above].min(low[cell]);
+            if low[cell] > discovered[above] {
+                // the passage from `above` is the only way into this subtree
+                let subtree = discovered[cell]..order.len();
+                if !subtree.contains(&discovered[self.end]) {
+                    found.extend_from_slice(&order[subtree]);
+                }
+            }
+        }
+
+        found.extend((0..grid.len()).filter(|&index| discovered[index] == unset && !self.filled[index]));
+        let any = !found.is_empty();
+        self.queue.extend(found);
+        any
+    }
+
+    /// Whatever is left, walked from the start.
+    fn finish(&mut self) {
+        let mut came_from = vec![None; self.grid.len()];
+        let mut seen = vec![false; self.grid.len()];
+        let mut queue = VecDeque::from([self.start]);
+        seen[self.start] = true;
+        while let Some(current) = queue.pop_front() {
+            if current == self.end {
+                self.path = trace_back(&came_from, self.end);
+                self.is_done = Done::Found;
+                return;
+            }
+            for direction in Direction::ALL {
+                if !self.grid.can_move(current, direction, self.traversal) {
+                    continue;
+                }
+                let next = self.grid.neighbour(current, direction).unwrap();
+                if self.filled[next] || seen[next] {
+                    continue;
+                }
+                seen[next] = true;
+                came_from[next] = Some(current);
+                queue.push_back(next);
+            }
+        }
+        self.is_done = Done::NotFound(true);
+    }
+}
+
+impl MazeSolver for DeadEndFiller {
+    fn new(grid: Arc<Grid>, start: usize, end: usize, _seed: u64) -> DeadEndFiller {
+        DeadEndFiller::with_mode(grid, start, end, FillMode::DeadEnds)
+    }
+
+    fn step(&mut self) {
+        if self.is_done != Done::NotFound(false) {
+            return;
+        }
+        while let Some(cell) = self.queue.pop_front() {
+            if !self.filled[cell] {
+                self.fill(cell);
+                return;
+            }
+        }
+        if self.mode == FillMode::CulDeSacs && self.find_cul_de_sacs() {
+            return;
+        }
+        self.finish();
+    }
+
+    fn done(&self) -> Done {
+        self.is_done
+    }
+
+    fn solution(&self) -> Vec<usize> {
+        self.path.clone()
+    }
+
+    fn filled(&self) -> Vec<usize> {
+        self.fill_order.clone()
+    }
+
+    fn report(&self) -> String {
+        format!("filled {} cells", self.fill_order.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(grid: &Arc<Grid>, mode: FillMode) -> DeadEndFiller {
+        let mut filler = DeadEndFiller::with_mode(Arc::clone(grid), 0, grid.len() - 1, mode);
+        while filler.done() == Done::NotFound(false) {
+            filler.step();
+        }
+        filler
+    }
+
+    #[test]
+    fn keeps_to_one_way_passages() {
+        // 0 <- 1 - 2, there's no getting from 0 to 2
+        let mut grid = Grid::new(3, 1);
+        grid.connect_one_way((1, 0), (0, 0));
+        grid.connect((2, 0), (1, 0));
+        let grid = Arc::new(grid);
+        for mode in [FillMode::DeadEnds, FillMode::CulDeSacs] {
+            let filler = run(&grid, mode);
+            assert_eq!(filler.done(), Done::NotFound(true), "{mode:?}");
+            assert!(filler.solution().is_empty(), "{mode:?}");
+        }
+
+        // and the right way round it's fine
+        let mut grid = Grid::new(3, 1);
+        grid.connect_one_way((0, 0), (1, 0));
+        grid.connect((2, 0), (1, 0));
+        let grid = Arc::new(grid);
+        for mode in [FillMode::DeadEnds, FillMode::CulDeSacs] {
+            assert_eq!(run(&grid, mode).solution(), vec![0, 1, 2], "{mode:?}");
+        }
+    }
+}
@@ -0,0 +1,824 @@
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use crate::Nodes::Grid;
+
+pub type Cell = (usize, usize);
+
+pub trait MazeGenerator {
+    /// Same seed, same maze.
+    fn new(seed: u64) -> Self where Self: Sized;
+    /// Does a bit of the work, usually opening one passage. The first call gets
+    /// a grid with all its walls up and every call after that the same grid.
+    /// Returns false once the maze is finished.
+    fn step(&mut self, grid: &mut Grid) -> bool;
+    fn generate(&mut self, grid: &mut Grid) {
+        while self.step(grid) {}
+    }
+
+    // what the window draws while the maze is being made
+
+    /// The cell it's working on.
+    fn current(&self) -> Option<Cell> {
+        None
+    }
+    /// Cells it's holding on to for later, like the backtracker's trail or Prim's frontier.
+    fn stack(&self) -> Vec<Cell> {
+        Vec::new()
+    }
+}
+
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum GeneratorKind {
+    RecursiveBacktracker,
+    Prim,
+    Kruskal,
+    Wilson,
+    AldousBroder,
+    HuntAndKill,
+    BinaryTree,
+    Sidewinder,
+    RecursiveDivision,
+}
+
+impl GeneratorKind {
+    pub const ALL: [GeneratorKind; 9] = [
+        GeneratorKind::RecursiveBacktracker,
+        GeneratorKind::Prim,
+        GeneratorKind::Kruskal,
+        GeneratorKind::Wilson,
+        GeneratorKind::AldousBroder,
+        GeneratorKind::HuntAndKill,
+        GeneratorKind::BinaryTree,
+        GeneratorKind::Sidewinder,
+        GeneratorKind::RecursiveDivision,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            GeneratorKind::RecursiveBacktracker => "recursive backtracker",
+            GeneratorKind::Prim => "prim",
+            GeneratorKind::Kruskal => "kruskal",
+            GeneratorKind::Wilson => "wilson",
+            GeneratorKind::AldousBroder => "aldous-broder",
+            GeneratorKind::HuntAndKill => "hunt and kill",
+            GeneratorKind::BinaryTree => "binary tree",
+            GeneratorKind::Sidewinder => "sidewinder",
+            GeneratorKind::RecursiveDivision => "recursive division",
+        }
+    }
+
+    /// Looks a generator up by name, ignoring case, spaces and dashes.
+    pub fn from_name(name: &str) -> Option<GeneratorKind> {
+        GeneratorKind::ALL.into_iter().find(|kind| simplify_name(kind.name()) == simplify_name(name))
+    }
+
+    /// A generator that hasn't started yet, for stepping through it.
+    pub fn build(&self, seed: u64) -> Box<dyn MazeGenerator> {
+        match self {
+            GeneratorKind::RecursiveBacktracker => Box::new(RecursiveBacktracker::new(seed)),
+            GeneratorKind::Prim => Box::new(Prim::new(seed)),
+            GeneratorKind::Kruskal => Box::new(Kruskal::new(seed)),
+            GeneratorKind::Wilson => Box::new(Wilson::new(seed)),
+            GeneratorKind::AldousBroder => Box::new(AldousBroder::new(seed)),
+            GeneratorKind::HuntAndKill => Box::new(HuntAndKill::new(seed)),
+            GeneratorKind::BinaryTree => Box::new(BinaryTree::new(seed)),
+            GeneratorKind::Sidewinder => Box::new(Sidewinder::new(seed)),
+            GeneratorKind::RecursiveDivision => Box::new(RecursiveDivision::new(seed)),
+        }
+    }
+
+    pub fn generate(&self, grid: &mut Grid, seed: u64) {
+        self.build(seed).generate(grid);
+    }
+}
+
+pub(crate) fn simplify_name(name: &str) -> String {
+    name.chars().filter(|c| c.is_alphanumeric()).flat_map(|c| c.to_lowercase()).collect()
+}
+
+fn neighbours(cell: Cell, width: usize, height: usize) -> Vec<Cell> {
+    let mut neighbours = Vec::with_capacity(4);
+    if cell.0 + 1 < width {
+        neighbours.push((cell.0 + 1, cell.1));
+    }
+    if cell.1 + 1 < height {
+        neighbours.push((cell.0, cell.1 + 1));
+    }
+    if cell.0 > 0 {
+        neighbours.push((cell.0 - 1, cell.1));
+    }
+    if cell.1 > 0 {
+        neighbours.push((cell.0, cell.1 - 1));
+    }
+    neighbours
+}
+
+fn pick<T: Copy>(list: &[T], rng: &mut StdRng) -> T {
+    list[rng.gen_range(0..list.len())]
+}
+
+fn shuffle<T>(list: &mut [T], rng: &mut StdRng) {
+    for i in (1..list.len()).rev() {
+        list.swap(i, rng.gen_range(0..=i));
+    }
+}
+
+/// Goes in random directions and when it gets stuck walks back along its
+/// stack until a cell has room to branch off.
+pub struct RecursiveBacktracker {
+    rng: StdRng,
+    started: bool,
+    width: usize,
+    visited: Vec<bool>, // by y * width + x, same as the grid
+    stack: Vec<usize>,
+}
+
+impl RecursiveBacktracker {
+    fn from_rng(rng: StdRng) -> Self {
+        RecursiveBacktracker {
+            rng,
+            started: false,
+            width: 0,
+            visited: Vec::new(),
+            stack: Vec::new(),
+        }
+    }
+
+    /// Walks until it opens one passage.
+    fn carve(&mut self, width: usize, height: usize, grid: &mut Grid) -> bool {
+        if !self.started {
+            self.started = true;
+            if width == 0 || height == 0 {
+                return false;
+            }
+            self.width = width;
+            self.visited = vec![false; width * height];
+            self.visited[0] = true;
+            self.stack.push(0);
+        }
+
+        while let Some(&current) = self.stack.last() {
+            let (x, y) = (current % width, current / width);
+            // no Vec here, this runs for every cell of huge mazes
+            let mut options = [0; 4];
+            let mut count = 0;
+            for (inside, next) in [
+                (x + 1 < width, current + 1),
+                (y + 1 < height, current + width),
+                (x > 0, current.wrapping_sub(1)),
+                (y > 0, current.wrapping_sub(width)),
+            ] {
+                if inside && !self.visited[next] {
+                    options[count] = next;
+                    count += 1;
+                }
+            }
+            if count == 0 {
+                self.stack.pop();
+                continue;
+            }
+
+            let next = options[self.rng.gen_range(0..count)];
+            self.visited[next] = true;
+            self.stack.push(next);
+            grid.connect((next % width, next / width), (x, y));
+            return true;
+        }
+        false
+    }
+}
+
+impl MazeGenerator for RecursiveBacktracker {
+    fn new(seed: u64) -> Self {
+        RecursiveBacktracker::from_rng(StdRng::seed_from_u64(seed))
+    }
+
+    fn step(&mut self, grid: &mut Grid) -> bool {
+        self.carve(grid.width, grid.height, grid)
+    }
+
+    fn current(&self) -> Option<Cell> {
+        self.stack.last().map(|&cell| (cell % self.width, cell / self.width))
+    }
+
+    fn stack(&self) -> Vec<Cell> {
+        self.stack.iter().map(|&cell| (cell % self.width, cell / self.width)).collect()
+    }
+}
+
+/// Grows the maze from (0, 0) by opening a random wall on its border each time.
+pub struct Prim {
+    rng: StdRng,
+    started: bool,
+    visited: Vec<Vec<bool>>,
+    frontier: Vec<(Cell, Cell)>, // (inside, outside) pairs
+    current: Option<Cell>,
+}
+
+impl MazeGenerator for Prim {
+    fn new(seed: u64) -> Self {
+        Prim {
+            rng: StdRng::seed_from_u64(seed),
+            started: false,
+            visited: Vec::new(),
+            frontier: Vec::new(),
+            current: None,
+        }
+    }
+
+    fn step(&mut self, grid: &mut Grid) -> bool {
+        let (width, height) = (grid.width, grid.height);
+        if !self.started {
+            self.started = true;
+            if width == 0 || height == 0 {
+                return false;
+            }
+            self.visited = vec![vec![false; height]; width];
+            self.visited[0][0] = true;
+            self.frontier = neighbours((0, 0), width, height).into_iter().map(|next| ((0, 0), next)).collect();
+        }
+
+        while !self.frontier.is_empty() {
+            let (from, to) = self.frontier.swap_remove(self.rng.gen_range(0..self.frontier.len()));
+            if self.visited[to.0][to.1] {
+                continue;
+            }
+            self.visited[to.0][to.1] = true;
+            grid.connect(to, from);
+            for next in neighbours(to, width, height) {
+                if !self.visited[next.0][next.1] {
+                    self.frontier.push((to, next));
+                }
+            }
+            self.current = Some(to);
+            return true;
+        }
+        self.current = None;
+        false
+    }
+
+    fn current(&self) -> Option<Cell> {
+        self.current
+    }
+
+    fn stack(&self) -> Vec<Cell> {
+        self.frontier.iter().map(|&(_, next)| next).filter(|next| !self.visited[next.0][next.1]).collect()
+    }
+}
+
+/// Knocks down walls in a random order as long as they join two separate parts of the maze.
+pub struct Kruskal {
+    rng: StdRng,
+    started: bool,
+    edges: Vec<(Cell, Cell)>, // shuffled, tried in order
+    next_edge: usize,
+    parent: Vec<usize>, // union find over x * height + y
+    current: Option<Cell>,
+}
+
+fn find(parent: &mut [usize], mut i: usize) -> usize {
+    while parent[i] != i {
+        parent[i] = parent[parent[i]];
+        i = parent[i];
+    }
+    i
+}
+
+impl MazeGenerator for Kruskal {
+    fn new(seed: u64) -> Self {
+        Kruskal {
+            rng: StdRng::seed_from_u64(seed),
+            started: false,
+            edges: Vec::new(),
+            next_edge: 0,
+            parent: Vec::new(),
+            current: None,
+        }
+    }
+
+    fn step(&mut self, grid: &mut Grid) -> bool {
+        let (width, height) = (grid.width, grid.height);
+        if !self.started {
+            self.started = true;
+            for x in 0..width {
+                for y in 0..height {
+                    if x + 1 < width {
+                        self.edges.push(((x, y), (x + 1, y)));
+                    }
+                    if y + 1 < height {
+                        self.edges.push(((x, y), (x, y + 1)));
+                    }
+                }
+            }
+            shuffle(&mut self.edges, &mut self.rng);
+            self.parent = (0..width * height).collect();
+        }
+
+        while let Some(&(a, b)) = self.edges.get(self.next_edge) {
+            self.next_edge += 1;
+            let set_a = find(&mut self.parent, a.0 * height + a.1);
+            let set_b = find(&mut self.parent, b.0 * height + b.1);
+            if set_a == set_b {
+                continue;
+            }
+            self.parent[set_a] = set_b;
+            grid.connect(a, b);
+            self.current = Some(a);
+            return true;
+        }
+        self.current = None;
+        false
+    }
+
+    fn current(&self) -> Option<Cell> {
+        self.current
+    }
+}
+
+/// Loop erased random walks from every cell until they hit the maze, gives a uniform spanning tree.
+pub struct Wilson {
+    rng: StdRng,
+    started: bool,
+    in_maze: Vec<Vec<bool>>,
+    // where the walk last left each cell, overwriting it is what erases the loops
+    next_step: Vec<Vec<Cell>>,
+    next_cell: usize, // x * height + y of the next cell to start a walk from
+    walk_start: Option<Cell>,
+    current: Cell,
+}
+
+impl MazeGenerator for Wilson {
+    fn new(seed: u64) -> Self {
+        Wilson {
+            rng: StdRng::seed_from_u64(seed),
+            started: false,
+            in_maze: Vec::new(),
+            next_step: Vec::new(),
+            next_cell: 0,
+            walk_start: None,
+            current: (0, 0),
+        }
+    }
+
+    /// One step of the walk, the whole walk gets carved when it hits the maze.
+    fn step(&mut self, grid: &mut Grid) -> bool {
+        let (width, height) = (grid.width, grid.height);
+        if !self.started {
+            self.started = true;
+            if width == 0 || height == 0 {
+                return false;
+            }
+            self.in_maze = vec![vec![false; height]; width];
+            self.in_maze[0][0] = true;
+            self.next_step = vec![vec![(0, 0); height]; width];
+        }
+
+        let start = match self.walk_start {
+            Some(start) => start,
+            None => {
+                let in_maze = &self.in_maze;
+                let Some(start) = (self.next_cell..width * height)
+                    .map(|index| (index / height, index % height))
+                    .find(|&(x, y)| !in_maze[x][y])
+                else {
+                    return false;
+                };
+                self.next_cell = start.0 * height + start.1 + 1;
+                self.walk_start = Some(start);
+                self.current = start;
+                start
+            }
+        };
+
+        let next = pick(&neighbours(self.current, width, height), &mut self.rng);
+        self.next_step[self.current.0][self.current.1] = next;
+        self.current = next;
+        if !self.in_maze[next.0][next.1] {
+            return true;
+        }
+
+        let mut current = start;
+        while !self.in_maze[current.0][current.1] {
+            self.in_maze[current.0][current.1] = true;
+            let next = self.next_step[current.0][current.1];
+            grid.connect(current, next);
+            current = next;
+        }
+        self.walk_start = None;
+        true
+    }
+
+    fn current(&self) -> Option<Cell> {
+        self.walk_start.map(|_| self.current)
+    }
+
+    /// The walk so far, with the loops already taken out.
+    fn stack(&self) -> Vec<Cell> {
+        let Some(mut cell) = self.walk_start else {
+            return Vec::new();
+        };
+        let mut walk = vec![cell];
+        while cell != self.current && walk.len() <= self.in_maze.len() * self.in_maze[0].len() {
+            cell = self.next_step[cell.0][cell.1];
+            walk.push(cell);
+        }
+        walk
+    }
+}
+
+/// Wanders randomly and connects every cell the first time it is entered.
+pub struct AldousBroder {
+    rng: StdRng,
+    started: bool,
+    visited: Vec<Vec<bool>>,
+    remaining: usize,
+    current: Cell,
+}
+
+impl MazeGenerator for AldousBroder {
+    fn new(seed: u64) -> Self {
+        AldousBroder {
+            rng: StdRng::seed_from_u64(seed),
+            started: false,
+            visited: Vec::new(),
+            remaining: 0,
+            current: (0, 0),
+        }
+    }
+
+    /// One move, which only opens a wall when it lands somewhere new.
+    fn step(&mut self, grid: &mut Grid) -> bool {
+        let (width, height) = (grid.width, grid.height);
+        if !self.started {
+            self.started = true;
+            if width == 0 || height == 0 {
+                return false;
+            }
+            self.visited = vec![vec![false; height]; width];
+            self.visited[0][0] = true;
+            self.remaining = width * height - 1;
+        }
+        if self.remaining == 0 {
+            return false;
+        }
+
+        let next = pick(&neighbours(self.current, width, height), &mut self.rng);
+        if !self.visited[next.0][next.1] {
+            self.visited[next.0][next.1] = true;
+            grid.connect(next, self.current);
+            self.remaining -= 1;
+        }
+        self.current = next;
+        true
+    }
+
+    fn current(&self) -> Option<Cell> {
+        Some(self.current)
+    }
+}
+
+/// Random walk until stuck, then scans for an unvisited cell next to the maze and continues from there.
+pub struct HuntAndKill {
+    rng: StdRng,
+    started: bool,
+    visited: Vec<bool>, // by y * width + x, so the hunt reads it in order
+    hunt_row: usize, // every row below this one is visited
+    current: Option<Cell>,
+}
+
+impl MazeGenerator for HuntAndKill {
+    fn new(seed: u64) -> Self {
+        HuntAndKill {
+            rng: StdRng::seed_from_u64(seed),
+            started: false,
+            visited: Vec::new(),
+            hunt_row: 0,
+            current: None,
+        }
+    }
+
+    fn step(&mut self, grid: &mut Grid) -> bool {
+        let (width, height) = (grid.width, grid.height);
+        if !self.started {
+            self.started = true;
+            if width == 0 || height == 0 {
+                return false;
+            }
+            self.visited = vec![false; width * height];
+            self.visited[0] = true;
+            self.current = Some((0, 0));
+        }
+        let Some(current) = self.current else {
+            return false;
+        };
+        let visited = &mut self.visited;
+        let is_visited = |visited: &[bool], (x, y): Cell| visited[y * width + x];
+
+        let unvisited: Vec<Cell> = neighbours(current, width, height).into_iter().filter(|&next| !is_visited(visited, next)).collect();
+        if !unvisited.is_empty() {
+            let next = pick(&unvisited, &mut self.rng);
+            visited[next.1 * width + next.0] = true;
+            grid.connect(next, current);
+            self.current = Some(next);
+            return true;
+        }
+
+        // hunt, starting from the first row that still has gaps
+        let mut found = None;
+        'hunt: for y in self.hunt_row..height {
+            let row = &visited[y * width..(y + 1) * width];
+            if y == self.hunt_row && row.iter().all(|&visited| visited) {
+                self.hunt_row += 1;
+                continue;
+            }
+            for (x, &done) in row.iter().enumerate() {
+                if done {
+                    continue;
+                }
+                let in_maze: Vec<Cell> = neighbours((x, y), width, height).into_iter().filter(|&next| is_visited(visited, next)).collect();
+                if !in_maze.is_empty() {
+                    found = Some(((x, y), pick(&in_maze, &mut self.rng)));
+                    break 'hunt;
+                }
+            }
+        }
+        let Some((cell, from)) = found else {
+            self.current = None;
+            return false;
+        };
+        visited[cell.1 * width + cell.0] = true;
+        grid.connect(cell, from);
+        self.current = Some(cell);
+        true
+    }
+
+    fn current(&self) -> Option<Cell> {
+        self.current
+    }
+}
+
+/// Every cell opens either up or right, leaves two long corridors along the top and right.
+pub struct BinaryTree {
+    rng: StdRng,
+    next_cell: usize, // x * height + y
+    current: Option<Cell>,
+}
+
+impl MazeGenerator for BinaryTree {
+    fn new(seed: u64) -> Self {
+        BinaryTree {
+            rng: StdRng::seed_from_u64(seed),
+            next_cell: 0,
+            current: None,
+        }
+    }
+
+    /// One cell at a time, column by column.
+    fn step(&mut self, grid: &mut Grid) -> bool {
+        let (width, height) = (grid.width, grid.height);
+        if self.next_cell >= width * height {
+            self.current = None;
+            return false;
+        }
+        let (x, y) = (self.next_cell / height, self.next_cell % height);
+        self.next_cell += 1;
+        self.current = Some((x, y));
+
+        let mut options = Vec::with_capacity(2);
+        if y + 1 < height {
+            options.push((x, y + 1));
+        }
+        if x + 1 < width {
+            options.push((x + 1, y));
+        }
+        // nothing to open in the top right corner
+        if !options.is_empty() {
+            grid.connect((x, y), pick(&options, &mut self.rng));
+        }
+        true
+    }
+
+    fn current(&self) -> Option<Cell> {
+        self.current
+    }
+}
+
+/// Row by row: carves runs to the right and closes each run with one opening up.
+pub struct Sidewinder {
+    rng: StdRng,
+    next_cell: usize, // y * width + x
+    run_start: usize,
+    current: Option<Cell>,
+}
+
+impl MazeGenerator for Sidewinder {
+    fn new(seed: u64) -> Self {
+        Sidewinder {
+            rng: StdRng::seed_from_u64(seed),
+            next_cell: 0,
+            run_start: 0,
+            current: None,
+        }
+    }
+
+    /// One cell at a time, row by row.
+    fn step(&mut self, grid: &mut Grid) -> bool {
+        let (width, height) = (grid.width, grid.height);
+        if self.next_cell >= width * height {
+            self.current = None;
+            return false;
+        }
+        let (x, y) = (self.next_cell % width, self.next_cell / width);
+        self.next_cell += 1;
+        self.current = Some((x, y));
+        if x == 0 {
+            self.run_start = 0;
+        }
+
+        let top_row = y + 1 == height;
+        let close_run = x + 1 == width || (!top_row && self.rng.gen_bool(0.5));
+        if close_run {
+            if !top_row {
+                let chosen = self.rng.gen_range(self.run_start..=x);
+                grid.connect((chosen, y), (chosen, y + 1));
+            }
+            self.run_start = x + 1;
+        } else {
+            grid.connect((x + 1, y), (x, y));
+        }
+        true
+    }
+
+    fn current(&self) -> Option<Cell> {
+        self.current
+    }
+
+    /// The run that's still open.
+    fn stack(&self) -> Vec<Cell> {
+        let Some((x, y)) = self.current else {
+            return Vec::new();
+        };
+        (self.run_start..=x).map(|run_x| (run_x, y)).collect()
+    }
+}
+
+/// Adds walls instead of carving: starts with every wall down and splits the
+/// grid in two with a wall that has one gap, then does the same to both halves.
+pub struct RecursiveDivision {
+    rng: StdRng,
+    started: bool,
+    chambers: Vec<(usize, usize, usize, usize)>, // x, y, width, height still to be split
+    last_wall: Vec<Cell>, // the cells just below or left of the last wall added
+}
+
+impl MazeGenerator for RecursiveDivision {
+    fn new(seed: u64) -> Self {
+        RecursiveDivision {
+            rng: StdRng::seed_from_u64(seed),
+            started: false,
+            chambers: Vec::new(),
+            last_wall: Vec::new(),
+        }
+    }
+
+    /// Adds one wall.
+    fn step(&mut self, grid: &mut Grid) -> bool {
+        if !self.started {
+            self.started = true;
+            grid.open_all();
+            self.chambers.push((0, 0, grid.width, grid.height));
+            return true;
+        }
+        // chambers one cell wide or high are done
+        let Some((x, y, width, height)) = std::iter::from_fn(|| self.chambers.pop()).find(|&(_, _, width, height)| width > 1 && height > 1) else {
+            self.last_wall.clear();
+            return false;
+        };
+
+        let horizontal = match width.cmp(&height) {
+            std::cmp::Ordering::Less => true,
+            std::cmp::Ordering::Greater => false,
+            std::cmp::Ordering::Equal => self.rng.gen(),
+        };
+        self.last_wall.clear();
+        if horizontal {
+            // the wall goes between rows `wall` and `wall + 1`
+            let wall = self.rng.gen_range(y..y + height - 1);
+            let gap = self.rng.gen_range(x..x + width);
+            for wall_x in (x..x + width).filter(|&wall_x| wall_x != gap) {
+                grid.disconnect((wall_x, wall), (wall_x, wall + 1));
+                self.last_wall.push((wall_x, wall));
+            }
+            self.chambers.push((x, y, width, wall + 1 - y));
+            self.chambers.push((x, wall + 1, width, y + height - wall - 1));
+        } else {
+            let wall = self.rng.gen_range(x..x + width - 1);
+            let gap = self.rng.gen_range(y..y + height);
+            for wall_y in (y..y + height).filter(|&wall_y| wall_y != gap) {
+                grid.disconnect((wall, wall_y), (wall + 1, wall_y));
+                self.last_wall.push((wall, wall_y));
+            }
+            self.chambers.push((x, y, wall + 1 - x, height));
+            self.chambers.push((wall + 1, y, x + width - wall - 1, height));
+        }
+        true
+    }
+
+    /// The wall that just went up.
+    fn stack(&self) -> Vec<Cell> {
+        self.last_wall.clone()
+    }
+}
+
+/// Opens walls at dead ends until `factor` (0 to 1) of them are gone, turning a
+/// perfect maze into one with loops. Dead ends next to each other get joined
+/// first since that gets rid of two at once.
+pub fn braid(grid: &mut Grid, factor: f32, seed: u64) {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let is_dead_end = |grid: &Grid, cell: Cell| grid.open_sides(grid.index(cell)) == 1;
+    let mut dead_ends: Vec<Cell> = (0..grid.len())
+        .map(|index| grid.coords(index))
+        .filter(|&cell| is_dead_end(grid, cell))
+        .collect();
+    let target = (dead_ends.len() as f32 * factor.clamp(0.0, 1.0)).round() as usize;
+    shuffle(&mut dead_ends, &mut rng);
+
+    let mut removed = 0;
+    for cell in dead_ends {
+        if removed >= target {
+            break;
+        }
+        // joined up with an earlier one already
+        if !is_dead_end(grid, cell) {
+            continue;
+        }
+        let closed: Vec<Cell> = neighbours(cell, grid.width, grid.height)
+            .into_iter()
+            .filter(|&next| {
+                let direction = grid.direction_to(grid.index(cell), grid.index(next)).unwrap();
+                !grid.is_open(grid.index(cell), direction)
+            })
+            .collect();
+        // the end of a one cell wide maze only has the outside left to open
+        if closed.is_empty() {
+            continue;
+        }
+        let also_dead: Vec<Cell> = closed.iter().copied().filter(|&next| is_dead_end(grid, next)).collect();
+        let next = if also_dead.is_empty() { pick(&closed, &mut rng) } else { pick(&also_dead, &mut rng) };
+        removed += if also_dead.is_empty() { 1 } else { 2 };
+        grid.connect(next, cell);
+    }
+}
+
+/// Fills `grid` with a recursive backtracker maze, drawing from `rng`.
+pub fn generate_maze(width: usize, height: usize, grid: &mut Grid, rng: &mut StdRng){
+    // hand it our rng and take it back after, so the caller's rng moves on like before
+    let mut backtracker = RecursiveBacktracker::from_rng(rng.clone());
+    while backtracker.carve(width, height, grid) {}
+    *rng = backtracker.rng;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Filling::dead_ends;
+    use crate::Nodes::Traversal;
+    use crate::Search::distance_field;
+
+    #[test]
+    fn full_braid_leaves_no_dead_ends() {
+        for kind in [GeneratorKind::RecursiveBacktracker, GeneratorKind::Kruskal, GeneratorKind::Prim] {
+            for seed in 0..10 {
+                let mut grid = Grid::new(15, 11);
+                kind.generate(&mut grid, seed);
+                assert!(!dead_ends(&grid).is_empty());
+                braid(&mut grid, 1.0, seed);
+                assert_eq!(dead_ends(&grid), Vec::<usize>::new(), "{} seed {seed}", kind.name());
+            }
+        }
+    }
+
+    #[test]
+    fn no_braid_leaves_the_maze_alone() {
+        for seed in 0..10 {
+            let mut grid = Grid::new(15, 11);
+            GeneratorKind::RecursiveBacktracker.generate(&mut grid, seed);
+            let perfect = grid.clone();
+            braid(&mut grid, 0.0, seed);
+            assert_eq!(grid, perfect);
+        }
+    }
+
+    #[test]
+    fn recursive_division_makes_a_spanning_tree() {
+        for (width, height) in [(7, 5), (9, 9), (2, 7), (7, 2), (1, 1), (1, 8), (8, 1)] {
+            for seed in 0..10 {
+                let mut grid = Grid::new(width, height);
+                GeneratorKind::RecursiveDivision.generate(&mut grid, seed);
+                // connected with one passage less than cells means no loops
+                let reached = distance_field(&grid, 0, Traversal::Undirected);
+                assert!(reached.iter().all(Option::is_some), "{width}x{height} seed {seed} isn't connected");
+                assert_eq!(grid.passages().count(), grid.len() - 1, "{width}x{height} seed {seed}");
+            }
+        }
+    }
+}
@@ -1,210 +1,379 @@
-use std::{borrow::Borrow, cell::{Ref, RefCell}, rc::Rc};
+use glam::{vec2, Vec2};
+use rand::seq::SliceRandom;
+use rand::Rng;
 
-use nannou::{draw::{mesh::vertex::Color, properties::spatial::position}, prelude::*};
-
-#[derive(PartialEq, Debug, Clone)]
-pub enum Connection {
-    In(Rc<Node>),
-    Out(Rc<Node>),
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
+pub enum Direction {
+    Up,
+    Down,
+    Right,
+    Left,
 }
 
-impl Connection {
-    pub fn get_position(&self) -> Vec2 {
+impl Direction {
+    pub const ALL: [Direction; 4] = [Direction::Up, Direction::Down, Direction::Right, Direction::Left];
+
+    /// The bit for this side in a cell's passage flags.
+    pub fn bit(&self) -> u8 {
         match self {
-            Connection::In(node) => node.position,
-            Connection::Out(node) => node.position,
+            Direction::Up => 0b0001,
+            Direction::Down => 0b0010,
+            Direction::Right => 0b0100,
+            Direction::Left => 0b1000,
         }
     }
 
-    pub fn is_out (&self) -> bool {
+    pub fn opposite(&self) -> Direction {
         match self {
-            Connection::Out(_) => true,
-            _ => false,
+            Direction::Up => Direction::Down,
+            Direction::Down => Direction::Up,
+            Direction::Right => Direction::Left,
+            Direction::Left => Direction::Right,
         }
     }
 
-    pub fn all_out(connections: Ref<Vec<Connection>>) -> Vec<Connection> {
-        connections.clone().into_iter().filter(|node| node.is_out()).collect()
+    /// The heading after a quarter turn counter clockwise.
+    pub fn turn_left(&self) -> Direction {
+        match self {
+            Direction::Up => Direction::Left,
+            Direction::Left => Direction::Down,
+            Direction::Down => Direction::Right,
+            Direction::Right => Direction::Up,
+        }
     }
 
-    pub fn get_out(&self) -> Rc<Node> {
+    /// The heading after a quarter turn clockwise.
+    pub fn turn_right(&self) -> Direction {
+        self.turn_left().opposite()
+    }
+
+    pub fn offset(&self) -> (isize, isize) {
         match self {
-            Connection::Out(node) => Rc::clone(node),
-            _ => panic!("Can't get out of in connection"),
+            Direction::Up => (0, 1),
+            Direction::Down => (0, -1),
+            Direction::Right => (1, 0),
+            Direction::Left => (-1, 0),
         }
     }
 }
 
-#[derive(PartialEq, Clone)]
-pub struct Node {
-    pub position: Vec2,
-    pub connected_nodes: RefCell<Vec<Connection>>,
-} 
+/// How solvers are allowed to walk through passages.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum Traversal {
+    /// Only the way the passage was carved, outgoing connections only.
+    Directed,
+    /// Both ways, except passages declared one-way with `Grid::connect_one_way`.
+    Undirected,
+}
 
-impl Node {
-    pub const DIST:f32 = 20.0;
-    pub const RAD:f32 = 10.0;
-    pub fn new(position: Vec2) -> Self {
+/// The maze itself: a flat array of cells, each with bitflags for its open sides.
+///
+/// Cells are indexed `y * width + x`. Like the old `Node::connect`, a passage is
+/// recorded as outgoing on one cell and incoming on the other, anything that is
+/// neither is a wall.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Grid {
+    pub width: usize,
+    pub height: usize,
+    out: Vec<u8>,
+    inn: Vec<u8>,
+    one_way: Vec<u8>,
+}
+
+impl Grid {
+    /// A grid with every wall up.
+    pub fn new(width: usize, height: usize) -> Grid {
+        Grid {
+            width,
+            height,
+            out: vec![0; width * height],
+            inn: vec![0; width * height],
+            one_way: vec![0; width * height],
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.width * self.height
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn index(&self, cell: (usize, usize)) -> usize {
+        cell.1 * self.width + cell.0
+    }
+
+    pub fn coords(&self, index: usize) -> (usize, usize) {
+        (index % self.width, index / self.width)
+    }
+
+    /// Where the cell sits when drawn, the grid is centered on the origin.
+    pub fn position(&self, index: usize) -> Vec2 {
+        let (x, y) = self.coords(index);
+        vec2(x as f32 - self.width as f32 / 2.0, y as f32 - self.height as f32 / 2.0)
+    }
+
+    /// The cell drawn at `position`, the inverse of `Grid::position`.
+    pub fn index_at(&self, position: Vec2) -> Option<usize> {
+        let x = (position.x + self.width as f32 / 2.0).round();
+        let y = (position.y + self.height as f32 / 2.0).round();
+        if x < 0.0 || y < 0.0 || x >= self.width as f32 || y >= self.height as f32 {
+            return None;
+        }
+        Some(self.index((x as usize, y as usize)))
+    }
+
+    pub fn node(&self, index: usize) -> Node<'_> {
         Node {
-            position,
-            connected_nodes: RefCell::new(Vec::new()),
+            grid: self,
+            index,
+            position: self.position(index),
         }
     }
-    fn get_neighbors_positions(node: &Node) -> Vec<Vec2> {
-        let directions = vec![
-            vec2(0.0, 1.0),  // Up
-            vec2(0.0, -1.0), // Down
-            vec2(1.0, 0.0),  // Right
-            vec2(-1.0, 0.0), // Left
-        ];
-        
-        directions.into_iter().map(|dir| node.position + dir).collect()
+
+    pub fn nodes(&self) -> impl Iterator<Item = Node<'_>> {
+        (0..self.len()).map(|index| self.node(index))
     }
-    pub fn contains(&self, other: &Node) -> bool {
-        if self.connected_nodes.borrow().len() == 0 {
-            return false;
+
+    /// The cell on the other side of `direction`, `None` past the edge of the grid.
+    pub fn neighbour(&self, index: usize, direction: Direction) -> Option<usize> {
+        let (x, y) = self.coords(index);
+        let (dx, dy) = direction.offset();
+        let x = x.checked_add_signed(dx).filter(|&x| x < self.width)?;
+        let y = y.checked_add_signed(dy).filter(|&y| y < self.height)?;
+        Some(self.index((x, y)))
+    }
+
+    pub fn direction_to(&self, from: usize, to: usize) -> Option<Direction> {
+        Direction::ALL.into_iter().find(|&direction| self.neighbour(from, direction) == Some(to))
+    }
+
+    /// Bitflags of the sides we can leave this cell through.
+    pub fn out_passages(&self, index: usize) -> u8 {
+        self.out[index]
+    }
+
+    /// Bitflags of the sides this cell was entered from.
+    pub fn in_passages(&self, index: usize) -> u8 {
+        self.inn[index]
+    }
+
+    /// Bitflags of the closed sides of a cell.
+    pub fn walls(&self, index: usize) -> u8 {
+        !(self.out[index] | self.inn[index]) & 0b1111
+    }
+
+    /// How many sides of a cell are open, 1 is a dead end.
+    pub fn open_sides(&self, index: usize) -> u8 {
+        4 - self.walls(index).count_ones() as u8
+    }
+
+    pub fn is_open(&self, index: usize, direction: Direction) -> bool {
+        self.walls(index) & direction.bit() == 0
+    }
+
+    /// Bitflags of the sides whose passage only goes one way.
+    pub fn one_way_passages(&self, index: usize) -> u8 {
+        self.one_way[index]
+    }
+
+    /// Whether we can walk out of a cell through `direction`.
+    pub fn can_move(&self, index: usize, direction: Direction, traversal: Traversal) -> bool {
+        if self.out[index] & direction.bit() != 0 {
+            return true;
         }
-        self.connected_nodes.borrow().iter().any(|conn| {
-            match conn {
-                Connection::Out(node) | Connection::In(node) => node.position == other.position,
-            }
+        match traversal {
+            Traversal::Directed => false,
+            Traversal::Undirected => self.inn[index] & !self.one_way[index] & direction.bit() != 0,
+        }
+    }
+
+    /// Every passage as `(from, to, one_way)`, `from` being the cell it goes out of.
+    pub fn passages(&self) -> impl Iterator<Item = (usize, usize, bool)> + '_ {
+        (0..self.len()).flat_map(move |index| {
+            Direction::ALL.into_iter()
+                .filter(move |direction| self.out[index] & direction.bit() != 0)
+                .map(move |direction| {
+                    let to = self.neighbour(index, direction).unwrap();
+                    (index, to, self.one_way[index] & direction.bit() != 0)
+                })
         })
     }
-    pub fn draw(&self, draw: &Draw, color: Srgba<u8>) {
-        
-        let wall_length = Node::DIST; // The length of each wall
-
-        // Draw the node itself (optional, e.g., as a small circle)
-        draw.ellipse()
-            .x_y(self.position.x * Node::DIST, self.position.y * Node::DIST)
-            .radius(Node::RAD)
-            .color(color);
-        
-        // Get positions of potential neighbors
-        let neighbors_positions = Self::get_neighbors_positions(self);
-
-        // Check which neighbors are connected and draw walls where they are not connected
-        for (i, neighbor_pos) in neighbors_positions.iter().enumerate() {
-            let is_connected = self.connected_nodes.borrow().iter().any(|conn| {
-                match conn {
-                    Connection::Out(node) | Connection::In(node) => node.position == *neighbor_pos,
+
+    /// Opens the wall between two neighbouring cells. `self_index` records it as
+    /// incoming, `other_index` as outgoing, the same way `Node::connect` always did.
+    pub fn connect(&mut self, self_index: (usize, usize), other_index: (usize, usize)) {
+        assert!(self_index != other_index, "Can't connect a node to itself");
+        let this = self.index(self_index);
+        let other = self.index(other_index);
+        let direction = self.direction_to(this, other).expect("Can only connect neighbouring nodes");
+        assert!(!self.is_open(this, direction), "Can't connect a node twice {:?}, {:?}", self_index, other_index);
+
+        // println!("connecting: {:?} to {:?}", self_index, other_index);
+        self.inn[this] |= direction.bit();
+        self.out[other] |= direction.opposite().bit();
+    }
+
+    /// Opens a passage that can only be walked from `from` into `to`, whatever the traversal.
+    pub fn connect_one_way(&mut self, from: (usize, usize), to: (usize, usize)) {
+        self.connect(to, from);
+        let to_index = self.index(to);
+        let from_index = self.index(from);
+        let direction = self.direction_to(from_index, to_index).unwrap();
+        self.one_way[from_index] |= direction.bit();
+        self.one_way[to_index] |= direction.opposite().bit();
+    }
+
+    /// Puts the wall between two neighbouring cells back up.
+    pub fn disconnect(&mut self, self_index: (usize, usize), other_index: (usize, usize)) {
+        let this = self.index(self_index);
+        let other = self.index(other_index);
+        let direction = self.direction_to(this, other).expect("Can only disconnect neighbouring nodes");
+        assert!(self.is_open(this, direction), "Can't disconnect nodes that aren't connected {:?}, {:?}", self_index, other_index);
+
+        for (index, side) in [(this, direction), (other, direction.opposite())] {
+            self.out[index] &= !side.bit();
+            self.inn[index] &= !side.bit();
+            self.one_way[index] &= !side.bit();
+        }
+    }
+
+    /// Takes every wall inside the grid down, the starting point for adding walls instead of carving.
+    pub fn open_all(&mut self) {
+        self.clear();
+        for y in 0..self.height {
+            for x in 0..self.width {
+                if x + 1 < self.width {
+                    self.connect((x + 1, y), (x, y));
                 }
-            });
-
-            if !is_connected {
-                // Draw the wall
-                match i {
-                    0 => { // Up
-                        draw.line()
-                            .start(self.position * Node::DIST + vec2(-wall_length / 2.0, wall_length / 2.0))
-                            .end(self.position * Node::DIST + vec2(wall_length / 2.0, wall_length / 2.0))
-                            .color(BLACK);
-                    },
-                    1 => { // Down
-                        draw.line()
-                            .start(self.position * Node::DIST + vec2(-wall_length / 2.0, -wall_length / 2.0))
-                            .end(self.position * Node::DIST + vec2(wall_length / 2.0, -wall_length / 2.0))
-                            .color(BLACK);
-                    },
-                    2 => { // Right
-                        draw.line()
-                            .start(self.position * Node::DIST + vec2(wall_length / 2.0, -wall_length / 2.0))
-                            .end(self.position * Node::DIST + vec2(wall_length / 2.0, wall_length / 2.0))
-                            .color(BLACK);
-                    },
-                    3 => { // Left
-                        draw.line()
-                            .start(self.position * Node::DIST + vec2(-wall_length / 2.0, -wall_length / 2.0))
-                            .end(self.position * Node::DIST + vec2(-wall_length / 2.0, wall_length / 2.0))
-                            .color(BLACK);
-                    },
-                    _ => (),
+                if y + 1 < self.height {
+                    self.connect((x, y + 1), (x, y));
                 }
             }
         }
     }
 
-    pub fn connect(self_index: (usize, usize), other_index: (usize, usize), nodes: &mut Vec<Vec<Rc<Node>>>) {
-        assert!(nodes[self_index.0][self_index.1].position != nodes[other_index.0][other_index.1].position, "Can't connect a node to itself");
-        assert!(!nodes[self_index.0][self_index.1].contains(nodes[other_index.0][other_index.1].borrow()), "Can't connect a node twice {:?}, {}", nodes[self_index.0][self_index.1].connected_nodes, nodes[other_index.0][other_index.1]);
-        
-        
-        
-        
-        // we connect a node
-        // out going for us, in going for them
-        let node = Rc::clone(&nodes[other_index.0][other_index.1]);
-        // let last_nodes = &mut nodes[self_index.0][self_index.1].connected_nodes;
-        nodes[self_index.0][self_index.1].connected_nodes.borrow_mut().push(Connection::In(Rc::clone(&node)));
-        
-        // println!("connecting: {:?} to {:?}", self_index, other_index);
-        
-        
-
-        nodes[other_index.0][other_index.1].connected_nodes.borrow_mut().push(Connection::Out(Rc::clone(&nodes[self_index.0][self_index.1])));
-        // println!("node: {:?} out is {}", nodes[other_index.0][other_index.1])
-
-    }
-
-    pub fn draw_connection(&self, draw: &Draw) {
-        // if self.position == vec2(-5.0, -5.0) {
-        //     println!("drawing connection for {:?}", self.connected_nodes);
-        // }
-        self.connected_nodes.borrow().iter().for_each(|node| {
-            match node {
-                Connection::In(vec) => draw.arrow()
-                    .start(vec.position * Self::DIST)
-                    .end(self.position * Self::DIST)
-                    .color(BLACK),
-                Connection::Out(vec) => draw.arrow()
-                        .start(self.position * Self::DIST)
-                        .end(vec.position * Self::DIST)
-                        .color(BLACK),
+    /// Puts every wall back up.
+    pub fn clear(&mut self) {
+        self.out.fill(0);
+        self.inn.fill(0);
+        self.one_way.fill(0);
+    }
+}
+
+/// A cheap view of one cell of a `Grid`.
+#[derive(Clone, Copy)]
+pub struct Node<'a> {
+    pub grid: &'a Grid,
+    pub index: usize,
+    pub position: Vec2,
+}
+
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum Connection<'a> {
+    In(Node<'a>),
+    Out(Node<'a>),
+}
+
+impl<'a> Connection<'a> {
+    pub fn get_position(&self) -> Vec2 {
+        match self {
+            Connection::In(node) => node.position,
+            Connection::Out(node) => node.position,
+        }
+    }
+
+    pub fn is_out (&self) -> bool {
+        matches!(self, Connection::Out(_))
+    }
+
+    pub fn all_out(connections: Vec<Connection<'a>>) -> Vec<Connection<'a>> {
+        connections.into_iter().filter(|node| node.is_out()).collect()
+    }
+
+    pub fn get_out(&self) -> Node<'a> {
+        match self {
+            Connection::Out(node) => *node,
+            _ => panic!("Can't get out of in connection"),
+        }
+    }
+}
+
+impl<'a> Node<'a> {
+    pub const DIST:f32 = 20.0;
+    pub const RAD:f32 = 10.0;
+
+    pub fn connected_nodes(&self) -> Vec<Connection<'a>> {
+        let mut connections = Vec::with_capacity(4);
+        for direction in Direction::ALL {
+            let Some(neighbour) = self.grid.neighbour(self.index, direction) else {
+                continue;
             };
-        });
-        
+            if self.grid.out_passages(self.index) & direction.bit() != 0 {
+                connections.push(Connection::Out(self.grid.node(neighbour)));
+            } else if self.grid.in_passages(self.index) & direction.bit() != 0 {
+                connections.push(Connection::In(self.grid.node(neighbour)));
+            }
+        }
+        connections
     }
 
-    pub fn print_nodes(&self) {
-        let position = self.position;
+    /// The nodes we can walk to from here.
+    pub fn neighbours(&self, traversal: Traversal) -> Vec<Node<'a>> {
+        Direction::ALL.into_iter()
+            .filter(|&direction| self.grid.can_move(self.index, direction, traversal))
+            .filter_map(|direction| self.grid.neighbour(self.index, direction))
+            .map(|index| self.grid.node(index))
+            .collect()
+    }
 
-        let connected_nodes = self.connected_nodes.clone();
-        println!("position: {}, connected nodes: {:?}", position, connected_nodes);
+    /// The nodes we can walk here from, the same as `neighbours` unless there are one-way passages.
+    pub fn predecessors(&self, traversal: Traversal) -> Vec<Node<'a>> {
+        Direction::ALL.into_iter()
+            .filter_map(|direction| Some((direction, self.grid.neighbour(self.index, direction)?)))
+            .filter(|&(direction, index)| self.grid.can_move(index, direction.opposite(), traversal))
+            .map(|(_, index)| self.grid.node(index))
+            .collect()
+    }
+
+    pub fn contains(&self, other: &Node) -> bool {
+        self.grid.direction_to(self.index, other.index).is_some_and(|direction| self.grid.is_open(self.index, direction))
+    }
+
+    pub fn print_nodes(&self) {
+        println!("position: {}, connected nodes: {:?}", self.position, self.connected_nodes());
     }
 
     pub fn is_connected(&self) -> bool{
-        self.connected_nodes.borrow().len() > 0
+        self.grid.walls(self.index) != 0b1111
     }
-}
 
-impl Node {
     pub fn able_to_move_to(&self, to: &Node) -> bool {
-        self.connected_nodes.borrow().iter().any(|node| {
-            match node {
-                Connection::Out(vec) => vec.position == to.position,
-                _ => false,
-            }
-        })
+        self.grid.direction_to(self.index, to.index).is_some_and(|direction| self.grid.out_passages(self.index) & direction.bit() != 0)
+    }
 
+    /// A random node we can walk to from here, `None` if we're walled in.
+    pub fn get_random_neighbour(&self, traversal: Traversal, rng: &mut impl Rng) -> Option<Node<'a>> {
+        self.neighbours(traversal).choose(rng).copied()
     }
-    pub fn get_random_neighbour(&self) -> Rc<Node> {
-        let index = random_range(0, self.connected_nodes.borrow().len());
-        match &self.connected_nodes.borrow()[index] {
-            Connection::Out(node) => Rc::clone(node),
-            _ => panic!("Can't get random neighbour"),
-        }
+}
+
+impl PartialEq for Node<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.index == other.index && std::ptr::eq(self.grid, other.grid)
     }
 }
 
-impl std::fmt::Display for Node {
+impl std::fmt::Display for Node<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.position)
     }
 }
 
-impl std::fmt::Debug for Node {
+impl std::fmt::Debug for Node<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.position)
-        
+
     }
-}
\ No newline at end of file
+}
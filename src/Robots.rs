@@ -0,0 +1,491 @@
+//! "Robot in a maze" solvers. They only know the walls around the cell they
+//! are standing on and which way they are facing, no map of the maze.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use crate::Astar::{Done, MazeSolver, Walker};
+use crate::Nodes::{Direction, Grid, Traversal};
+
+/// Which wall a `WallFollower` keeps its hand on.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum Hand {
+    Left,
+    Right,
+}
+
+/// The walk with every loop cut out, what's left is a path from the start.
+pub struct Route {
+    pub cells: Vec<usize>,
+    position: Vec<Option<usize>>, // where a cell sits in `cells`
+}
+
+impl Route {
+    pub fn new(start: usize, len: usize) -> Route {
+        let mut position = vec![None; len];
+        position[start] = Some(0);
+        Route {
+            cells: vec![start],
+            position,
+        }
+    }
+
+    /// Adds the next cell of the walk, going back to a cell on the route drops the loop in between.
+    pub fn push(&mut self, cell: usize) {
+        if let Some(at) = self.position[cell] {
+            for dropped in self.cells.drain(at + 1..) {
+                self.position[dropped] = None;
+            }
+            return;
+        }
+        self.position[cell] = Some(self.cells.len());
+        self.cells.push(cell);
+    }
+}
+
+/// The side facing roughly towards `to`.
+fn heading_towards(grid: &Grid, from: usize, to: usize) -> Direction {
+    let diff = grid.position(to) - grid.position(from);
+    if diff.x.abs() >= diff.y.abs() {
+        if diff.x >= 0.0 { Direction::Right } else { Direction::Left }
+    } else if diff.y > 0.0 {
+        Direction::Up
+    } else {
+        Direction::Down
+    }
+}
+
+fn slot(direction: Direction) -> usize {
+    direction.bit().trailing_zeros() as usize
+}
+
+/// Keeps one hand on the wall and walks until it finds the goal or comes back
+/// to a spot it already stood on facing the same way, which means it's going round an island.
+pub struct WallFollower {
+    pub grid: Arc<Grid>,
+    pub start: usize,
+    pub end: usize,
+    pub traversal: Traversal,
+    pub hand: Hand,
+    pub heading: Direction,
+    pub walker: Walker,
+    pub route: Route,
+    pub looping: bool,
+    seen: Vec<u8>, // headings we already had when standing on each cell
+}
+
+impl WallFollower {
+    pub fn with_hand(grid: Arc<Grid>, start: usize, end: usize, hand: Hand) -> WallFollower {
+        let mut seen = vec![0; grid.len()];
+        let heading = heading_towards(&grid, start, end);
+        seen[start] |= heading.bit();
+        WallFollower {
+            route: Route::new(start, grid.len()),
+            grid,
+            start,
+            end,
+            traversal: Traversal::Undirected,
+            hand,
+            heading,
+            walker: Walker::new(start, Vec::new()),
+            looping: false,
+            seen,
+        }
+    }
+}
+
+impl MazeSolver for WallFollower {
+    fn new(grid: Arc<Grid>, start: usize, end: usize, _seed: u64) -> WallFollower {
+        WallFollower::with_hand(grid, start, end, Hand::Left)
+    }
+
+    fn step(&mut self) {
+        if self.walker.is_done != Done::NotFound(false) {
+            return;
+        }
+        let current = self.walker.current_node;
+        if current == self.end {
+            self.walker.is_done = Done::Found;
+            return;
+        }
+
+        let heading = self.heading;
+        let turns = match self.hand {
+            Hand::Left => [heading.turn_left(), heading, heading.turn_right(), heading.opposite()],
+            Hand::Right => [heading.turn_right(), heading, heading.turn_left(), heading.opposite()],
+        };
+        let Some(direction) = turns.into_iter().find(|&direction| self.grid.can_move(current, direction, self.traversal)) else {
+            // walled in on every side
+            self.walker.is_done = Done::NotFound(true);
+            return;
+        };
+        let next = self.grid.neighbour(current, direction).unwrap();
+        self.heading = direction;
+        self.walker.step(next);
+        self.route.push(next);
+
+        if next == self.end {
+            self.walker.is_done = Done::Found;
+        } else if self.seen[next] & direction.bit() != 0 {
+            self.looping = true;
+            self.walker.is_done = Done::NotFound(true);
+        }
+        self.seen[next] |= direction.bit();
+    }
+
+    fn done(&self) -> Done {
+        self.walker.is_done
+    }
+
+    fn solution(&self) -> Vec<usize> {
+        match self.walker.is_done {
+            Done::Found => self.route.cells.clone(),
+            Done::NotFound(_) => Vec::new(),
+        }
+    }
+
+    fn visited(&self) -> Vec<usize> {
+        self.walker.path.clone()
+    }
+
+    fn walkers(&self) -> &[Walker] {
+        std::slice::from_ref(&self.walker)
+    }
+
+    fn report(&self) -> String {
+        if self.looping {
+            return format!("went round in a loop after {} moves", self.walker.path.len());
+        }
+        format!("{} moves", self.walker.path.len())
+    }
+}
+
+/// Heads one way (towards the goal at the start) and when blocked follows the
+/// wall with its right hand, counting turns, until the count is back to zero.
+pub struct Pledge {
+    pub grid: Arc<Grid>,
+    pub start: usize,
+    pub end: usize,
+    pub traversal: Traversal,
+    pub preferred: Direction,
+    pub heading: Direction,
+    pub turns: i32, // quarter turns, left is +1
+    pub walker: Walker,
+    pub route: Route,
+    pub looping: bool,
+    walls_followed: usize, // bumped every time the count gets back to 0 and it lets go of the wall
+    seen: HashMap<(usize, Direction), (usize, i32)>, // which wall and the turn count when it was first there
+    seen_straight: HashSet<(usize, Direction)>, // places it was in with a count of 0
+}
+
+impl MazeSolver for Pledge {
+    fn new(grid: Arc<Grid>, start: usize, end: usize, _seed: u64) -> Pledge {
+        let preferred = heading_towards(&grid, start, end);
+        Pledge {
+            route: Route::new(start, grid.len()),
+            grid,
+            start,
+            end,
+            traversal: Traversal::Undirected,
+            preferred,
+            heading: preferred,
+            turns: 0,
+            walker: Walker::new(start, Vec::new()),
+            looping: false,
+            walls_followed: 0,
+            seen: HashMap::new(),
+            seen_straight: HashSet::from([(start, preferred)]),
+        }
+    }
+
+    fn step(&mut self) {
+        if self.walker.is_done != Done::NotFound(false) {
+            return;
+        }
+        let current = self.walker.current_node;
+        if current == self.end {
+            self.walker.is_done = Done::Found;
+            return;
+        }
+
+        let heading = self.heading;
+        let options = if self.turns == 0 {
+            // going the preferred way, when blocked turn left so the wall ends up on the right
+            [(heading, 0), (heading.turn_left(), 1), (heading.opposite(), 2), (heading.turn_right(), 3)]
+        } else {
+            [(heading.turn_right(), -1), (heading, 0), (heading.turn_left(), 1), (heading.opposite(), 2)]
+        };
+        let Some((direction, turn)) = options.into_iter().find(|&(direction, _)| self.grid.can_move(current, direction, self.traversal)) else {
+            self.walker.is_done = Done::NotFound(true);
+            return;
+        };
+        let next = self.grid.neighbour(current, direction).unwrap();
+        self.turns += turn;
+        self.heading = direction;
+        self.walker.step(next);
+        self.route.push(next);
+
+        if next == self.end {
+            self.walker.is_done = Done::Found;
+            return;
+        }
+        if self.turns == 0 {
+            if turn != 0 {
+                // the count just got back to 0, so it lets go of the wall
+                self.walls_followed += 1;
+            }
+            if !self.seen_straight.insert((next, direction)) {
+                self.looping = true;
+                self.walker.is_done = Done::NotFound(true);
+            }
+            return;
+        }
+        // along one wall the count can only be off by whole turns. the same count is a plain
+        // loop, a count further from 0 means it's circling an island and will never let go
+        let wall = self.walls_followed;
+        match self.seen.get(&(next, direction)) {
+            Some(&(seen_wall, first)) if seen_wall == wall && (first == self.turns || (self.turns - first).signum() == self.turns.signum()) => {
+                self.looping = true;
+                self.walker.is_done = Done::NotFound(true);
+            }
+            Some(&(seen_wall, _)) if seen_wall == wall => {}
+            _ => {
+                self.seen.insert((next, direction), (wall, self.turns));
+            }
+        }
+    }
+
+    fn done(&self) -> Done {
+        self.walker.is_done
+    }
+
+    fn solution(&self) -> Vec<usize> {
+        match self.walker.is_done {
+            Done::Found => self.route.cells.clone(),
+            Done::NotFound(_) => Vec::new(),
+        }
+    }
+
+    fn visited(&self) -> Vec<usize> {
+        self.walker.path.clone()
+    }
+
+    fn walkers(&self) -> &[Walker] {
+        std::slice::from_ref(&self.walker)
+    }
+
+    fn report(&self) -> String {
+        if self.looping {
+            return format!("went round in a loop after {} moves", self.walker.path.len());
+        }
+        format!("{} moves, turn count {}", self.walker.path.len(), self.turns)
+    }
+}
+
+/// Marks every passage it walks through and never walks one a third time.
+/// Always finds the goal if there is a way to it.
+pub struct Tremaux {
+    pub grid: Arc<Grid>,
+    pub start: usize,
+    pub end: usize,
+    pub traversal: Traversal,
+    pub heading: Direction,
+    pub walker: Walker,
+    pub route: Route,
+    pub marks: Vec<[u8; 4]>, // times each side of a cell was walked through, see `slot`
+    entry: Option<Direction>, // the side of the current cell we came in through
+}
+
+impl MazeSolver for Tremaux {
+    fn new(grid: Arc<Grid>, start: usize, end: usize, _seed: u64) -> Tremaux {
+        Tremaux {
+            route: Route::new(start, grid.len()),
+            marks: vec![[0; 4]; grid.len()],
+            heading: heading_towards(&grid, start, end),
+            grid,
+            start,
+            end,
+            traversal: Traversal::Undirected,
+            walker: Walker::new(start, Vec::new()),
+            entry: None,
+        }
+    }
+
+    fn step(&mut self) {
+        if self.walker.is_done != Done::NotFound(false) {
+            return;
+        }
+        let current = self.walker.current_node;
+        if current == self.end {
+            self.walker.is_done = Done::Found;
+            return;
+        }
+
+        let heading = self.heading;
+        let marks = self.marks[current];
+        let exits: Vec<Direction> = [heading, heading.turn_left(), heading.turn_right(), heading.opposite()]
+            .into_iter()
+            .filter(|&direction| self.grid.can_move(current, direction, self.traversal))
+            .collect();
+
+        // came down a new passage into a place we've already been, go straight back
+        let turn_back = self.entry
+            .filter(|&entry| marks[slot(entry)] == 1 && exits.contains(&entry))
+            .filter(|&entry| exits.iter().any(|&direction| direction != entry && marks[slot(direction)] > 0));
+        let direction = turn_back
+            .or_else(|| exits.iter().copied().find(|&direction| marks[slot(direction)] == 0))
+            .or_else(|| exits.iter().copied().find(|&direction| marks[slot(direction)] == 1));
+        let Some(direction) = direction else {
+            // every passage walked twice, the goal can't be reached
+            self.walker.is_done = Done::NotFound(true);
+            return;
+        };
+
+        let next = self.grid.neighbour(current, direction).unwrap();
+        self.marks[current][slot(direction)] += 1;
+        self.marks[next][slot(direction.opposite())] += 1;
+        self.entry = Some(direction.opposite());
+        self.heading = direction;
+        self.walker.step(next);
+        self.route.push(next);
+
+        if next == self.end {
+            self.walker.is_done = Done::Found;
+        }
+    }
+
+    fn done(&self) -> Done {
+        self.walker.is_done
+    }
+
+    fn solution(&self) -> Vec<usize> {
+        match self.walker.is_done {
+            Done::Found => self.route.cells.clone(),
+            Done::NotFound(_) => Vec::new(),
+        }
+    }
+
+    fn visited(&self) -> Vec<usize> {
+        self.walker.path.clone()
+    }
+
+    fn walkers(&self) -> &[Walker] {
+        std::slice::from_ref(&self.walker)
+    }
+
+    fn report(&self) -> String {
+        format!("{} moves", self.walker.path.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Generators::{braid, GeneratorKind};
+
+    fn maze(seed: u64, braid_factor: f32) -> Arc<Grid> {
+        let mut grid = Grid::new(15, 11);
+        GeneratorKind::RecursiveBacktracker.generate(&mut grid, seed);
+        braid(&mut grid, braid_factor, seed);
+        Arc::new(grid)
+    }
+
+    // a goal in the middle, so there's a way round it to circle
+    fn run(grid: Arc<Grid>) -> Pledge {
+        let end = grid.len() / 2;
+        let mut pledge = Pledge::new(grid, 0, end, 0);
+        for _ in 0..100_000 {
+            if pledge.done() != Done::NotFound(false) {
+                break;
+            }
+            pledge.step();
+        }
+        pledge
+    }
+
+    #[test]
+    fn pledge_stops_on_braided_mazes() {
+        for seed in 0..20 {
+            let grid = maze(seed, 1.0);
+            let cells = grid.len();
+            let pledge = run(grid);
+            assert_ne!(pledge.done(), Done::NotFound(false), "seed {seed} is still going at turn count {}", pledge.turns);
+            assert!(pledge.seen.len() <= 4 * cells, "seed {seed} remembered {} states", pledge.seen.len());
+        }
+    }
+
+    fn assert_walkable(grid: &Grid, path: &[usize], start: usize, end: usize) {
+        assert_eq!((path.first(), path.last()), (Some(&start), Some(&end)));
+        for pair in path.windows(2) {
+            let direction = grid.direction_to(pair[0], pair[1]).expect("cells next to each other");
+            assert!(grid.can_move(pair[0], direction, Traversal::Undirected), "{pair:?} goes through a wall");
+        }
+    }
+
+    #[test]
+    fn wall_followers_solve_perfect_mazes() {
+        for seed in 0..10 {
+            let grid = maze(seed, 0.0);
+            let end = grid.len() - 1;
+            for hand in [Hand::Left, Hand::Right] {
+                let mut follower = WallFollower::with_hand(Arc::clone(&grid), 0, end, hand);
+                for _ in 0..10_000 {
+                    follower.step();
+                }
+                assert_eq!(follower.done(), Done::Found, "seed {seed}, {hand:?} hand");
+                assert_walkable(&grid, &follower.solution(), 0, end);
+            }
+        }
+    }
+
+    // a loop round the island in the middle of a 5x5 grid, with the goal off the far side of it
+    fn island() -> Grid {
+        let mut grid = Grid::new(5, 5);
+        let ring = [(1, 1), (2, 1), (3, 1), (3, 2), (3, 3), (2, 3), (1, 3), (1, 2), (1, 1)];
+        for pair in ring.windows(2) {
+            grid.connect(pair[1], pair[0]);
+        }
+        // a way in, a dead end off the ring and the goal sticking out into the island
+        grid.connect((0, 1), (0, 0));
+        grid.connect((1, 1), (0, 1));
+        grid.connect((4, 1), (3, 1));
+        grid.connect((4, 0), (4, 1));
+        grid.connect((2, 2), (2, 3));
+        grid
+    }
+
+    #[test]
+    fn tremaux_finds_the_goal_inside_a_loop() {
+        let grid = Arc::new(island());
+        let end = grid.index((2, 2));
+        let mut tremaux = Tremaux::new(Arc::clone(&grid), 0, end, 0);
+        for _ in 0..1000 {
+            tremaux.step();
+        }
+        assert_eq!(tremaux.done(), Done::Found);
+        assert_walkable(&grid, &tremaux.solution(), 0, end);
+        assert!(tremaux.marks.iter().flatten().all(|&marks| marks <= 2), "{:?}", tremaux.marks);
+    }
+
+    #[test]
+    fn tremaux_gives_up_after_walking_everything_twice() {
+        let grid = Arc::new(island());
+        // the corner the ring doesn't reach
+        let end = grid.index((4, 4));
+        let mut tremaux = Tremaux::new(Arc::clone(&grid), 0, end, 0);
+        for _ in 0..1000 {
+            tremaux.step();
+        }
+        assert_eq!(tremaux.done(), Done::NotFound(true));
+        assert!(tremaux.marks.iter().flatten().all(|&marks| marks <= 2), "{:?}", tremaux.marks);
+    }
+
+    #[test]
+    fn pledge_still_solves_perfect_mazes() {
+        for seed in 0..20 {
+            let pledge = run(maze(seed, 0.0));
+            assert_eq!(pledge.done(), Done::Found, "seed {seed}");
+            assert!(!pledge.looping);
+        }
+    }
+}
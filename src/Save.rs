@@ -0,0 +1,240 @@
+//! Saving mazes to JSON and loading them back, so they can be shared. Text
+//! and image mazes can be loaded through here too.
+
+use std::{fmt, fs, io, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::Ascii::{from_ascii, ParseError};
+use crate::Bitmap::{load_png, BitmapError};
+use crate::Nodes::Grid;
+
+/// Bumped whenever the layout of `MazeDocument` changes.
+pub const FORMAT_VERSION: u32 = 1;
+
+/// The most cells a loaded maze can have, a 4000 by 4000 maze. Anything bigger
+/// is far more likely a typo than a real maze.
+pub const MAX_CELLS: usize = 16_000_000;
+
+/// Everything needed to get the same maze back.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct MazeDocument {
+    pub version: u32,
+    pub width: usize,
+    pub height: usize,
+    pub start: (usize, usize),
+    pub goal: (usize, usize),
+    /// Only there when the maze came out of a generator.
+    #[serde(default)]
+    pub seed: Option<u64>,
+    #[serde(default)]
+    pub generator: Option<String>,
+    pub passages: Vec<Passage>,
+}
+
+/// An open wall, walked from `from` to `to` (the old Out/In connection).
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Passage {
+    pub from: (usize, usize),
+    pub to: (usize, usize),
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub one_way: bool,
+}
+
+#[derive(Debug)]
+pub enum LoadError {
+    Io(io::Error),
+    Json(serde_json::Error),
+    Version(u32),
+    Invalid(String), // parses fine but isn't a maze we can build
+    Text(ParseError),
+    Image(BitmapError),
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoadError::Io(error) => write!(f, "couldn't read the file: {error}"),
+            LoadError::Json(error) => write!(f, "bad json: {error}"),
+            LoadError::Version(version) => write!(f, "unknown maze version {version}, expected {FORMAT_VERSION}"),
+            LoadError::Invalid(message) => write!(f, "bad maze: {message}"),
+            LoadError::Text(error) => write!(f, "bad text maze: {error}"),
+            LoadError::Image(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl std::error::Error for LoadError {}
+
+impl MazeDocument {
+    pub fn from_maze(grid: &Grid, start: usize, goal: usize, seed: Option<u64>, generator: Option<&str>) -> MazeDocument {
+        MazeDocument {
+            version: FORMAT_VERSION,
+            width: grid.width,
+            height: grid.height,
+            start: grid.coords(start),
+            goal: grid.coords(goal),
+            seed,
+            generator: generator.map(str::to_string),
+            passages: grid.passages()
+                .map(|(from, to, one_way)| Passage {
+                    from: grid.coords(from),
+                    to: grid.coords(to),
+                    one_way,
+                })
+                .collect(),
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("a maze always turns into json")
+    }
+
+    pub fn from_json(text: &str) -> Result<MazeDocument, LoadError> {
+        // check the version first so an old file doesn't fail on some missing field
+        #[derive(Deserialize)]
+        struct Versioned {
+            version: u32,
+        }
+        let versioned: Versioned = serde_json::from_str(text).map_err(LoadError::Json)?;
+        if versioned.version != FORMAT_VERSION {
+            return Err(LoadError::Version(versioned.version));
+        }
+        serde_json::from_str(text).map_err(LoadError::Json)
+    }
+
+    /// Builds the grid back up, checking every passage on the way.
+    pub fn to_grid(&self) -> Result<Grid, LoadError> {
+        if self.width == 0 || self.height == 0 {
+            return Err(LoadError::Invalid("width and height have to be at least 1".to_string()));
+        }
+        // checked before Grid::new, which would overflow or try to allocate it all
+        match self.width.checked_mul(self.height) {
+            Some(cells) if cells <= MAX_CELLS => {}
+            _ => return Err(LoadError::Invalid(format!("{}x{} is too big, mazes can have at most {MAX_CELLS} cells", self.width, self.height))),
+        }
+        let inside = |(x, y): (usize, usize)| x < self.width && y < self.height;
+        for (name, cell) in [("start", self.start), ("goal", self.goal)] {
+            if !inside(cell) {
+                return Err(LoadError::Invalid(format!("{name} {cell:?} is outside the maze")));
+            }
+        }
+
+        let mut grid = Grid::new(self.width, self.height);
+        for passage in &self.passages {
+            if !inside(passage.from) || !inside(passage.to) {
+                return Err(LoadError::Invalid(format!("passage {:?} -> {:?} is outside the maze", passage.from, passage.to)));
+            }
+            let from = grid.index(passage.from);
+            let to = grid.index(passage.to);
+            let Some(direction) = grid.direction_to(from, to) else {
+                return Err(LoadError::Invalid(format!("{:?} and {:?} aren't next to each other", passage.from, passage.to)));
+            };
+            if grid.is_open(from, direction) {
+                return Err(LoadError::Invalid(format!("passage {:?} -> {:?} is there twice", passage.from, passage.to)));
+            }
+            if passage.one_way {
+                grid.connect_one_way(passage.from, passage.to);
+            } else {
+                grid.connect(passage.to, passage.from);
+            }
+        }
+        Ok(grid)
+    }
+}
+
+pub fn save_json(path: impl AsRef<Path>, document: &MazeDocument) -> io::Result<()> {
+    fs::write(path, document.to_json())
+}
+
+pub fn load_json(path: impl AsRef<Path>) -> Result<MazeDocument, LoadError> {
+    let text = fs::read_to_string(path).map_err(LoadError::Io)?;
+    MazeDocument::from_json(&text)
+}
+
+/// Loads json, text (`.txt`) or image (`.png`) mazes, going by the extension.
+///
+/// Images don't have a start or goal, so they're put on the first and last gap in
+/// the outer wall, or in opposite corners without gaps.
+pub fn load_maze_file(path: impl AsRef<Path>) -> Result<MazeDocument, LoadError> {
+    let path = path.as_ref();
+    let extension = path.extension().and_then(|extension| extension.to_str()).unwrap_or("").to_lowercase();
+    match extension.as_str() {
+        "txt" => {
+            let text = fs::read_to_string(path).map_err(LoadError::Io)?;
+            let maze = from_ascii(&text).map_err(LoadError::Text)?;
+            Ok(MazeDocument::from_maze(&maze.grid, maze.start, maze.goal, None, None))
+        }
+        "png" => {
+            let maze = load_png(path).map_err(LoadError::Image)?;
+            let start = maze.entrances.first().copied().unwrap_or(0);
+            let goal = match maze.entrances.as_slice() {
+                [_, .., last] => *last,
+                _ => maze.grid.len() - 1,
+            };
+            Ok(MazeDocument::from_maze(&maze.grid, start, goal, None, None))
+        }
+        _ => load_json(path),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Generators::GeneratorKind;
+    use crate::Nodes::Direction;
+
+    fn maze() -> Grid {
+        let mut grid = Grid::new(8, 6);
+        GeneratorKind::Kruskal.generate(&mut grid, 5);
+        // a perfect maze has walls left over, open one of them one way
+        let from = (0..grid.len()).find(|&cell| grid.neighbour(cell, Direction::Right).is_some() && !grid.is_open(cell, Direction::Right)).unwrap();
+        let to = grid.neighbour(from, Direction::Right).unwrap();
+        grid.connect_one_way(grid.coords(from), grid.coords(to));
+        grid
+    }
+
+    #[test]
+    fn round_trips_json() {
+        let grid = maze();
+        let document = MazeDocument::from_maze(&grid, 0, grid.len() - 1, Some(5), Some("kruskal"));
+        assert!(document.passages.iter().any(|passage| passage.one_way));
+
+        let loaded = MazeDocument::from_json(&document.to_json()).unwrap();
+        assert_eq!(loaded, document);
+        assert_eq!(loaded.to_grid().unwrap(), grid);
+    }
+
+    #[test]
+    fn rejects_other_versions() {
+        let document = MazeDocument::from_maze(&maze(), 0, 1, None, None);
+        let json = document.to_json().replace(&format!("\"version\": {FORMAT_VERSION}"), "\"version\": 2");
+        assert!(matches!(MazeDocument::from_json(&json), Err(LoadError::Version(2))));
+    }
+
+    #[test]
+    fn rejects_huge_mazes() {
+        let mut document = MazeDocument::from_maze(&Grid::new(1, 1), 0, 0, None, None);
+        document.width = 100_000;
+        document.height = 100_000;
+        assert!(matches!(document.to_grid(), Err(LoadError::Invalid(_))));
+    }
+
+    #[test]
+    fn rejects_sizes_that_overflow() {
+        let json = MazeDocument::from_maze(&Grid::new(1, 1), 0, 0, None, None)
+            .to_json()
+            .replace("\"width\": 1", &format!("\"width\": {}", usize::MAX))
+            .replace("\"height\": 1", "\"height\": 2");
+        let document = MazeDocument::from_json(&json).unwrap();
+        assert_eq!(document.width, usize::MAX);
+        assert!(matches!(document.to_grid(), Err(LoadError::Invalid(_))));
+    }
+
+    #[test]
+    fn rejects_passages_that_skip_a_cell() {
+        let mut document = MazeDocument::from_maze(&Grid::new(3, 1), 0, 2, None, None);
+        document.passages.push(Passage { from: (0, 0), to: (2, 0), one_way: false });
+        assert!(matches!(document.to_grid(), Err(LoadError::Invalid(_))));
+    }
+}
@@ -0,0 +1,454 @@
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+use crate::Astar::{trace_back, Done, MazeSolver, Walker};
+use crate::Nodes::{Grid, Traversal};
+
+/// Breadth first search, always finds the shortest path.
+pub struct Bfs {
+    pub grid: Arc<Grid>,
+    pub start: usize,
+    pub end: usize,
+    pub traversal: Traversal,
+    pub path: Vec<usize>, // the solution, filled in once the end is found
+    pub queue: VecDeque<usize>,
+    pub closed: Vec<usize>, // in the order they were expanded
+    pub is_done: Done,
+    visited: Vec<bool>, // set when a node is queued, so nothing gets queued twice
+    came_from: Vec<Option<usize>>,
+}
+
+/// Depth first search, follows one corridor until it dead ends before trying another.
+pub struct Dfs {
+    pub grid: Arc<Grid>,
+    pub start: usize,
+    pub end: usize,
+    pub traversal: Traversal,
+    pub path: Vec<usize>,
+    pub stack: Vec<(usize, Option<usize>)>, // node and the node that pushed it
+    pub closed: Vec<usize>,
+    pub is_done: Done,
+    visited: Vec<bool>, // set when a node is popped, a node can sit on the stack more than once
+    came_from: Vec<Option<usize>>,
+}
+
+impl MazeSolver for Bfs {
+    fn new(grid: Arc<Grid>, start: usize, end: usize, _seed: u64) -> Bfs {
+        let mut visited = vec![false; grid.len()];
+        visited[start] = true;
+        Bfs {
+            came_from: vec![None; grid.len()],
+            grid,
+            start,
+            end,
+            traversal: Traversal::Undirected,
+            path: Vec::new(),
+            queue: VecDeque::from([start]),
+            closed: Vec::new(),
+            is_done: Done::NotFound(false),
+            visited,
+        }
+    }
+
+    /// Expands the node at the front of the queue.
+    fn step(&mut self) {
+        if self.is_done != Done::NotFound(false) {
+            return;
+        }
+        let Some(current) = self.queue.pop_front() else {
+            self.is_done = Done::NotFound(true);
+            return;
+        };
+        self.closed.push(current);
+
+        if current == self.end {
+            self.path = trace_back(&self.came_from, self.end);
+            self.is_done = Done::Found;
+            return;
+        }
+
+        for next in self.grid.node(current).neighbours(self.traversal) {
+            if self.visited[next.index] {
+                continue;
+            }
+            self.visited[next.index] = true;
+            self.came_from[next.index] = Some(current);
+            self.queue.push_back(next.index);
+        }
+    }
+
+    fn done(&self) -> Done {
+        self.is_done
+    }
+
+    fn solution(&self) -> Vec<usize> {
+        self.path.clone()
+    }
+
+    fn visited(&self) -> Vec<usize> {
+        self.closed.clone()
+    }
+
+    fn frontier(&self) -> Vec<usize> {
+        self.queue.iter().copied().collect()
+    }
+}
+
+impl MazeSolver for Dfs {
+    fn new(grid: Arc<Grid>, start: usize, end: usize, _seed: u64) -> Dfs {
+        Dfs {
+            visited: vec![false; grid.len()],
+            came_from: vec![None; grid.len()],
+            grid,
+            start,
+            end,
+            traversal: Traversal::Undirected,
+            path: Vec::new(),
+            stack: vec![(start, None)],
+            closed: Vec::new(),
+            is_done: Done::NotFound(false),
+        }
+    }
+
+    /// Expands the node on top of the stack.
+    fn step(&mut self) {
+        if self.is_done != Done::NotFound(false) {
+            return;
+        }
+        let (current, parent) = loop {
+            match self.stack.pop() {
+                Some((node, _)) if self.visited[node] => continue, // pushed again from somewhere else
+                Some(entry) => break entry,
+                None => {
+                    self.is_done = Done::NotFound(true);
+                    return;
+                }
+            }
+        };
+        self.visited[current] = true;
+        self.came_from[current] = parent;
+        self.closed.push(current);
+
+        if current == self.end {
+            self.path = trace_back(&self.came_from, self.end);
+            self.is_done = Done::Found;
+            return;
+        }
+
+        for next in self.grid.node(current).neighbours(self.traversal) {
+            if !self.visited[next.index] {
+                self.stack.push((next.index, Some(current)));
+            }
+        }
+    }
+
+    fn done(&self) -> Done {
+        self.is_done
+    }
+
+    fn solution(&self) -> Vec<usize> {
+        self.path.clone()
+    }
+
+    fn visited(&self) -> Vec<usize> {
+        self.closed.clone()
+    }
+
+    fn frontier(&self) -> Vec<usize> {
+        self.stack.iter()
+            .map(|&(node, _)| node)
+            .filter(|&node| !self.visited[node])
+            .collect()
+    }
+}
+
+/// One half of a `Bidirectional` search, a breadth first search of its own.
+pub struct SearchSide {
+    pub queue: VecDeque<usize>,
+    pub closed: Vec<usize>,
+    visited: Vec<bool>,
+    came_from: Vec<Option<usize>>,
+}
+
+impl SearchSide {
+    fn new(from: usize, len: usize) -> SearchSide {
+        let mut visited = vec![false; len];
+        visited[from] = true;
+        SearchSide {
+            queue: VecDeque::from([from]),
+            closed: Vec::new(),
+            visited,
+            came_from: vec![None; len],
+        }
+    }
+}
+
+/// Searches from the start and the end at the same time and stops where they meet.
+///
+/// Each step expands one node from whichever side has the smaller queue. Stopping at
+/// the first meeting can be a step too long on some graphs, but every loop in a grid
+/// has an even length, so here the joined path is always a shortest one.
+pub struct Bidirectional {
+    pub grid: Arc<Grid>,
+    pub start: usize,
+    pub end: usize,
+    pub traversal: Traversal,
+    pub path: Vec<usize>,
+    pub forward: SearchSide,
+    pub backward: SearchSide,
+    pub meeting: Option<usize>,
+    pub expansions: usize,
+    pub is_done: Done,
+    walkers: [Walker; 2], // the node each side expanded last, so `view` can draw the two heads
+}
+
+impl Bidirectional {
+    /// Joins the two halves, `from_start` is reached by the forward side and `from_end` by the backward side.
+    fn join(&mut self, from_start: usize, from_end: usize, meeting: usize) {
+        let mut back_half = trace_back(&self.backward.came_from, from_end);
+        back_half.reverse();
+        self.path = trace_back(&self.forward.came_from, from_start);
+        self.path.extend(back_half);
+        self.meeting = Some(meeting);
+        self.is_done = Done::Found;
+        for walker in &mut self.walkers {
+            walker.current_node = meeting;
+            walker.is_done = Done::Found;
+        }
+    }
+}
+
+impl MazeSolver for Bidirectional {
+    fn new(grid: Arc<Grid>, start: usize, end: usize, _seed: u64) -> Bidirectional {
+        Bidirectional {
+            forward: SearchSide::new(start, grid.len()),
+            backward: SearchSide::new(end, grid.len()),
+            grid,
+            start,
+            end,
+            traversal: Traversal::Undirected,
+            path: Vec::new(),
+            meeting: None,
+            expansions: 0,
+            is_done: Done::NotFound(false),
+            walkers: [Walker::new(start, Vec::new()), Walker::new(end, Vec::new())],
+        }
+    }
+
+    fn step(&mut self) {
+        if self.is_done != Done::NotFound(false) {
+            return;
+        }
+        if self.start == self.end {
+            self.path = vec![self.start];
+            self.meeting = Some(self.start);
+            self.is_done = Done::Found;
+            return;
+        }
+        // once either side runs dry there is no way through
+        if self.forward.queue.is_empty() || self.backward.queue.is_empty() {
+            self.is_done = Done::NotFound(true);
+            for walker in &mut self.walkers {
+                walker.is_done = Done::NotFound(true);
+            }
+            return;
+        }
+
+        let forwards = self.forward.queue.len() <= self.backward.queue.len();
+        let (side, other) = if forwards {
+            (&mut self.forward, &self.backward)
+        } else {
+            (&mut self.backward, &self.forward)
+        };
+        let current = side.queue.pop_front().unwrap();
+        side.closed.push(current);
+        self.expansions += 1;
+        self.walkers[if forwards { 0 } else { 1 }].current_node = current;
+
+        let node = self.grid.node(current);
+        // the backward side walks the passages the wrong way round
+        let next_nodes = if forwards { node.neighbours(self.traversal) } else { node.predecessors(self.traversal) };
+        let mut meeting = None;
+        for next in next_nodes {
+            if other.visited[next.index] {
+                meeting = Some(next.index);
+                break;
+            }
+            if side.visited[next.index] {
+                continue;
+            }
+            side.visited[next.index] = true;
+            side.came_from[next.index] = Some(current);
+            side.queue.push_back(next.index);
+        }
+
+        if let Some(meeting) = meeting {
+            if forwards {
+                self.join(current, meeting, meeting);
+            } else {
+                self.join(meeting, current, meeting);
+            }
+        }
+    }
+
+    fn done(&self) -> Done {
+        self.is_done
+    }
+
+    fn solution(&self) -> Vec<usize> {
+        self.path.clone()
+    }
+
+    fn visited(&self) -> Vec<usize> {
+        self.forward.closed.iter().chain(&self.backward.closed).copied().collect()
+    }
+
+    fn frontier(&self) -> Vec<usize> {
+        self.forward.queue.iter().copied().collect()
+    }
+
+    fn back_frontier(&self) -> Vec<usize> {
+        self.backward.queue.iter().copied().collect()
+    }
+
+    fn walkers(&self) -> &[Walker] {
+        &self.walkers
+    }
+
+    fn report(&self) -> String {
+        match self.meeting {
+            Some(meeting) => format!("met at {:?} after {} expansions", self.grid.coords(meeting), self.expansions),
+            None => format!("{} expansions", self.expansions),
+        }
+    }
+}
+
+/// How many steps each cell is from `start`, `None` where it can't be reached.
+pub fn distance_field(grid: &Grid, start: usize, traversal: Traversal) -> Vec<Option<usize>> {
+    let mut distances = vec![None; grid.len()];
+    distances[start] = Some(0);
+    let mut queue = VecDeque::from([start]);
+    while let Some(current) = queue.pop_front() {
+        let distance = distances[current].unwrap() + 1;
+        for next in grid.node(current).neighbours(traversal) {
+            if distances[next.index].is_none() {
+                distances[next.index] = Some(distance);
+                queue.push_back(next.index);
+            }
+        }
+    }
+    distances
+}
+
+/// The reachable cell furthest away in a `distance_field`, and how far it is.
+pub fn farthest(distances: &[Option<usize>]) -> Option<(usize, usize)> {
+    distances.iter()
+        .enumerate()
+        .filter_map(|(index, distance)| Some((index, (*distance)?)))
+        .max_by_key(|&(_, distance)| distance)
+}
+
+/// A shortest path from `start` to `end` found with a breadth first search, all in one go.
+pub fn shortest_path(grid: &Grid, start: usize, end: usize, traversal: Traversal) -> Option<Vec<usize>> {
+    let mut came_from = vec![None; grid.len()];
+    let mut seen = vec![false; grid.len()];
+    seen[start] = true;
+    let mut queue = VecDeque::from([start]);
+    while let Some(current) = queue.pop_front() {
+        if current == end {
+            return Some(trace_back(&came_from, end));
+        }
+        for next in grid.node(current).neighbours(traversal) {
+            if !seen[next.index] {
+                seen[next.index] = true;
+                came_from[next.index] = Some(current);
+                queue.push_back(next.index);
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Generators::{braid, GeneratorKind};
+
+    fn braided(seed: u64) -> Arc<Grid> {
+        let mut grid = Grid::new(15, 11);
+        GeneratorKind::RecursiveBacktracker.generate(&mut grid, seed);
+        braid(&mut grid, 0.7, seed);
+        Arc::new(grid)
+    }
+
+    fn solve(mut solver: impl MazeSolver) -> Vec<usize> {
+        while solver.done() == Done::NotFound(false) {
+            solver.step();
+        }
+        assert_eq!(solver.done(), Done::Found);
+        solver.solution()
+    }
+
+    fn assert_walkable(grid: &Grid, path: &[usize], start: usize, end: usize) {
+        assert_eq!((path.first(), path.last()), (Some(&start), Some(&end)));
+        for pair in path.windows(2) {
+            let direction = grid.direction_to(pair[0], pair[1]).expect("cells next to each other");
+            assert!(grid.can_move(pair[0], direction, Traversal::Undirected), "{pair:?} goes through a wall");
+        }
+    }
+
+    #[test]
+    fn bfs_and_dfs_solve_braided_mazes() {
+        for seed in 0..20 {
+            let grid = braided(seed);
+            let distances = distance_field(&grid, 0, Traversal::Undirected);
+            for end in [grid.len() - 1, grid.len() / 2] {
+                let bfs = solve(Bfs::new(Arc::clone(&grid), 0, end, seed));
+                assert_walkable(&grid, &bfs, 0, end);
+                assert_eq!(Some(bfs.len() - 1), distances[end], "seed {seed}");
+
+                let dfs = solve(Dfs::new(Arc::clone(&grid), 0, end, seed));
+                assert_walkable(&grid, &dfs, 0, end);
+                assert!(dfs.len() >= bfs.len());
+            }
+        }
+    }
+
+    #[test]
+    fn bidirectional_joins_a_shortest_path() {
+        // corridors with an odd and even number of steps, so the two sides meet on
+        // a cell and across a wall
+        for length in 2..10 {
+            let mut corridor = Grid::new(length, 1);
+            for x in 1..length {
+                corridor.connect((x, 0), (x - 1, 0));
+            }
+            let corridor = Arc::new(corridor);
+            let path = solve(Bidirectional::new(Arc::clone(&corridor), 0, length - 1, 0));
+            assert_eq!(path, (0..length).collect::<Vec<_>>());
+        }
+        // a loop, the long way round has to lose
+        let mut ring = Grid::new(6, 2);
+        for x in 1..6 {
+            ring.connect((x, 0), (x - 1, 0));
+            ring.connect((x, 1), (x - 1, 1));
+        }
+        ring.connect((0, 1), (0, 0));
+        ring.connect((5, 1), (5, 0));
+        let ring = Arc::new(ring);
+        for end in 1..ring.len() {
+            let path = solve(Bidirectional::new(Arc::clone(&ring), 0, end, 0));
+            assert_walkable(&ring, &path, 0, end);
+            assert_eq!(Some(path.len() - 1), distance_field(&ring, 0, Traversal::Undirected)[end], "end {end}");
+        }
+        for seed in 0..20 {
+            let grid = braided(seed);
+            let distances = distance_field(&grid, 0, Traversal::Undirected);
+            for end in [grid.len() - 1, grid.len() / 2, grid.len() / 2 + 1] {
+                let path = solve(Bidirectional::new(Arc::clone(&grid), 0, end, seed));
+                assert_walkable(&grid, &path, 0, end);
+                assert_eq!(Some(path.len() - 1), distances[end], "seed {seed}, end {end}");
+            }
+        }
+    }
+}
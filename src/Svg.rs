@@ -0,0 +1,189 @@
+//! Vector pictures of a maze, laid out the same way the window draws it.
+
+use std::fmt::Write;
+use std::{fs, io, path::Path};
+
+use crate::Astar::Walker;
+use crate::Nodes::{Direction, Grid, Node};
+
+/// Colors are anything SVG understands, like `"black"` or `"#1ecbe1"`. They're
+/// escaped on the way out, so a stray `"` or `&` can't break the file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SvgStyle {
+    pub stroke_width: f32,
+    pub wall_color: String,
+    pub path_color: String,
+    pub walker_color: String,
+    pub background: Option<String>,
+    /// Space around the maze, in the same units as `Node::DIST`.
+    pub margin: f32,
+}
+
+impl Default for SvgStyle {
+    fn default() -> Self {
+        SvgStyle {
+            stroke_width: 2.0,
+            wall_color: "black".to_string(),
+            path_color: "blue".to_string(),
+            walker_color: "red".to_string(),
+            background: Some("white".to_string()),
+            margin: Node::DIST / 2.0,
+        }
+    }
+}
+
+/// Makes text safe to put inside an attribute.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('"', "&quot;").replace('\'', "&apos;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+/// Draws the walls, then the `path` and every walker's path as arrows. Pass empty
+/// slices to leave them out.
+pub fn to_svg(grid: &Grid, style: &SvgStyle, path: &[usize], walkers: &[Walker]) -> String {
+    let half = Node::DIST / 2.0;
+    let width = grid.width as f32 * Node::DIST + 2.0 * style.margin;
+    let height = grid.height as f32 * Node::DIST + 2.0 * style.margin;
+    // the window has y going up, svg has it going down
+    let left = grid.position(0).x * Node::DIST - half - style.margin;
+    let top = grid.position(grid.len() - 1).y * Node::DIST + half + style.margin;
+    let point = |cell: usize, dx: f32, dy: f32| {
+        let position = grid.position(cell) * Node::DIST;
+        (position.x + dx - left, top - (position.y + dy))
+    };
+
+    let mut svg = String::new();
+    writeln!(svg, r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}">"#).unwrap();
+    let wall_color = escape(&style.wall_color);
+    let path_color = escape(&style.path_color);
+    let walker_color = escape(&style.walker_color);
+    // only the arrow heads that get used
+    let arrows_for = [
+        ("path-arrow", &path_color, path.len() > 1),
+        ("walker-arrow", &walker_color, walkers.iter().any(|walker| walker.path.len() > 1)),
+    ];
+    if arrows_for.iter().any(|&(_, _, used)| used) {
+        writeln!(svg, "<defs>").unwrap();
+        for (id, color, _) in arrows_for.iter().filter(|&&(_, _, used)| used) {
+            writeln!(svg, r#"<marker id="{id}" markerWidth="4" markerHeight="4" refX="4" refY="2" orient="auto"><path d="M0,0 L4,2 L0,4 z" fill="{color}"/></marker>"#).unwrap();
+        }
+        writeln!(svg, "</defs>").unwrap();
+    }
+    if let Some(background) = &style.background {
+        writeln!(svg, r#"<rect width="100%" height="100%" fill="{}"/>"#, escape(background)).unwrap();
+    }
+
+    // each cell draws its bottom and left wall, the top row and right column also close the outside
+    let mut walls = String::new();
+    for cell in 0..grid.len() {
+        let (x, y) = grid.coords(cell);
+        let mut sides = vec![Direction::Down, Direction::Left];
+        if y == grid.height - 1 {
+            sides.push(Direction::Up);
+        }
+        if x == grid.width - 1 {
+            sides.push(Direction::Right);
+        }
+        for direction in sides {
+            if grid.is_open(cell, direction) {
+                continue;
+            }
+            let ((x1, y1), (x2, y2)) = match direction {
+                Direction::Up => (point(cell, -half, half), point(cell, half, half)),
+                Direction::Down => (point(cell, -half, -half), point(cell, half, -half)),
+                Direction::Right => (point(cell, half, -half), point(cell, half, half)),
+                Direction::Left => (point(cell, -half, -half), point(cell, -half, half)),
+            };
+            write!(walls, "M{x1:.1},{y1:.1}L{x2:.1},{y2:.1}").unwrap();
+        }
+    }
+    writeln!(svg, r#"<path d="{walls}" stroke="{wall_color}" stroke-width="{}" stroke-linecap="square" fill="none"/>"#, style.stroke_width).unwrap();
+
+    let mut arrows = |cells: &[usize], color: &str, marker: &str, width: f32| {
+        for step in cells.windows(2) {
+            let (x1, y1) = point(step[0], 0.0, 0.0);
+            let (x2, y2) = point(step[1], 0.0, 0.0);
+            writeln!(svg, r#"<line x1="{x1:.1}" y1="{y1:.1}" x2="{x2:.1}" y2="{y2:.1}" stroke="{color}" stroke-width="{width}" marker-end="url(#{marker})"/>"#).unwrap();
+        }
+    };
+    for walker in walkers {
+        arrows(&walker.path, &walker_color, "walker-arrow", style.stroke_width / 2.0);
+    }
+    arrows(path, &path_color, "path-arrow", style.stroke_width);
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+pub fn save_svg(path: impl AsRef<Path>, grid: &Grid, style: &SvgStyle, solution: &[usize], walkers: &[Walker]) -> io::Result<()> {
+    fs::write(path, to_svg(grid, style, solution, walkers))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn style(margin: f32) -> SvgStyle {
+        SvgStyle { margin, ..SvgStyle::default() }
+    }
+
+    fn wall_segments(svg: &str) -> Vec<&str> {
+        let walls = svg.lines().find(|line| line.starts_with("<path d=")).unwrap();
+        let d = walls.split('"').nth(1).unwrap();
+        d.split('M').skip(1).collect()
+    }
+
+    #[test]
+    fn draws_each_wall_once() {
+        // two cells side by side, 20 units each
+        let mut grid = Grid::new(2, 1);
+        let closed = wall_segments(&to_svg(&grid, &style(0.0), &[], &[])).len();
+        assert_eq!(closed, 7);
+
+        grid.connect((1, 0), (0, 0));
+        let svg = to_svg(&grid, &style(0.0), &[], &[]);
+        let segments = wall_segments(&svg);
+        assert_eq!(segments.len(), 6);
+        // the outside of the left cell, and no wall left in the middle
+        for wall in ["0.0,20.0L20.0,20.0", "0.0,20.0L0.0,0.0", "0.0,0.0L20.0,0.0"] {
+            assert!(segments.contains(&wall), "{wall} missing from {segments:?}");
+        }
+        assert!(!segments.iter().any(|segment| segment.starts_with("20.0,20.0L20.0,0.0")));
+    }
+
+    #[test]
+    fn margin_grows_the_picture() {
+        let svg = to_svg(&Grid::new(2, 1), &style(5.0), &[], &[]);
+        assert!(svg.starts_with(r#"<svg xmlns="http://www.w3.org/2000/svg" width="50" height="30" viewBox="0 0 50 30">"#), "{svg}");
+        // the left wall moves in by the margin
+        assert!(wall_segments(&svg).contains(&"5.0,25.0L5.0,5.0"));
+    }
+
+    #[test]
+    fn only_draws_paths_it_is_given() {
+        let mut grid = Grid::new(2, 1);
+        grid.connect((1, 0), (0, 0));
+        let bare = to_svg(&grid, &style(0.0), &[], &[]);
+        assert!(!bare.contains("<line") && !bare.contains("<marker"));
+
+        let with_path = to_svg(&grid, &style(0.0), &[0, 1], &[]);
+        assert_eq!(with_path.matches("url(#path-arrow)").count(), 1);
+        assert!(!with_path.contains("walker-arrow"));
+
+        let walker = Walker::new(1, vec![0, 1]);
+        let with_walker = to_svg(&grid, &style(0.0), &[], &[walker]);
+        assert_eq!(with_walker.matches("url(#walker-arrow)").count(), 1);
+        assert!(!with_walker.contains("path-arrow"));
+    }
+
+    #[test]
+    fn escapes_colors() {
+        let style = SvgStyle {
+            wall_color: r#"red" onload="x"#.to_string(),
+            background: Some("a&b".to_string()),
+            ..SvgStyle::default()
+        };
+        let svg = to_svg(&Grid::new(1, 1), &style, &[], &[]);
+        assert!(svg.contains(r#"stroke="red&quot; onload=&quot;x""#));
+        assert!(svg.contains(r#"fill="a&amp;b""#));
+    }
+}
@@ -0,0 +1,218 @@
+//! Generates and solves a maze without opening a window.
+//!
+//! maze-cli --size 30 --generator prim --solver astar --heuristic euclidean --seed 42
+
+use std::process::ExitCode;
+use std::sync::Arc;
+use std::time::Instant;
+
+use second_try::Analysis::analyse;
+use second_try::Ascii::to_ascii;
+use second_try::Astar::{Done, Heuristic, MazeSolver, SolverKind};
+use second_try::Generators::{braid, GeneratorKind};
+use second_try::Bitmap::save_png;
+use second_try::Nodes::Grid;
+use second_try::Save::load_maze_file;
+use second_try::Svg::{save_svg, SvgStyle};
+
+const USAGE: &str = "usage: maze-cli [--size N] [--width N] [--height N] [--generator NAME] [--solver NAME] [--heuristic NAME] [--seed N] [--braid PERCENT] [--max-steps N] [--ascii PATH] [--svg PATH] [--svg-stroke-width N] [--svg-wall-color COLOR] [--svg-path-color COLOR] [--svg-walker-color COLOR] [--svg-background COLOR] [--svg-margin N] [--svg-no-path] [--png PATH] [--load PATH]
+
+generators: recursive-backtracker, prim, kruskal, wilson, aldous-broder, hunt-and-kill, binary-tree, sidewinder,
+    recursive-division
+solvers: astar, random-mouse, bfs, dfs, bidirectional, left-hand, right-hand, pledge, tremaux,
+    dead-end-filling, cul-de-sac-filling
+heuristics: manhattan, euclidean, chebyshev, zero (or dijkstra)
+--ascii writes the solved maze as text, - prints it
+--svg and --png write the solved maze as a picture
+--svg-stroke-width, --svg-wall-color, --svg-path-color, --svg-walker-color, --svg-background and --svg-margin
+    style the svg, colors are anything svg understands like black or #1ecbe1 (none for no background),
+    --svg-no-path leaves the solution and the walkers out
+--load solves a saved maze (.json, .txt or .png) instead of generating one";
+
+struct Options {
+    width: usize,
+    height: usize,
+    generator: GeneratorKind,
+    solver: SolverKind,
+    heuristic: Heuristic,
+    seed: u64,
+    braid: f32,
+    max_steps: usize,
+    ascii: Option<String>,
+    svg: Option<String>,
+    svg_style: SvgStyle,
+    svg_path: bool, // draw the solution on the svg
+    png: Option<String>,
+    load: Option<String>,
+}
+
+fn parse_args() -> Result<Options, String> {
+    let mut options = Options {
+        width: 30,
+        height: 30,
+        generator: GeneratorKind::RecursiveBacktracker,
+        solver: SolverKind::AStar,
+        heuristic: Heuristic::Manhattan,
+        seed: rand::random(),
+        braid: 0.0,
+        max_steps: 10_000_000,
+        ascii: None,
+        svg: None,
+        svg_style: SvgStyle::default(),
+        svg_path: true,
+        png: None,
+        load: None,
+    };
+
+    let mut args = std::env::args().skip(1);
+    while let Some(flag) = args.next() {
+        if flag == "--help" || flag == "-h" {
+            return Err(String::new());
+        }
+        // the only flag without a value
+        if flag == "--svg-no-path" {
+            options.svg_path = false;
+            continue;
+        }
+        let value = args.next().ok_or(format!("missing value for {flag}"))?;
+        match flag.as_str() {
+            "--size" => {
+                options.width = value.parse().map_err(|_| format!("bad size: {value}"))?;
+                options.height = options.width;
+            }
+            "--width" => options.width = value.parse().map_err(|_| format!("bad width: {value}"))?,
+            "--height" => options.height = value.parse().map_err(|_| format!("bad height: {value}"))?,
+            "--generator" => options.generator = GeneratorKind::from_name(&value).ok_or(format!("unknown generator: {value}"))?,
+            "--solver" => options.solver = SolverKind::from_name(&value).ok_or(format!("unknown solver: {value}"))?,
+            "--heuristic" => options.heuristic = Heuristic::from_name(&value).ok_or(format!("unknown heuristic: {value}"))?,
+            "--seed" => options.seed = value.parse().map_err(|_| format!("bad seed: {value}"))?,
+            "--braid" => options.braid = value.parse().map_err(|_| format!("bad braid percentage: {value}"))?,
+            "--max-steps" => options.max_steps = value.parse().map_err(|_| format!("bad step count: {value}"))?,
+            "--ascii" => options.ascii = Some(value),
+            "--svg" => options.svg = Some(value),
+            "--svg-stroke-width" => options.svg_style.stroke_width = value.parse().map_err(|_| format!("bad stroke width: {value}"))?,
+            "--svg-wall-color" => options.svg_style.wall_color = value,
+            "--svg-path-color" => options.svg_style.path_color = value,
+            "--svg-walker-color" => options.svg_style.walker_color = value,
+            "--svg-background" => options.svg_style.background = (value != "none").then_some(value),
+            "--svg-margin" => options.svg_style.margin = value.parse().map_err(|_| format!("bad margin: {value}"))?,
+            "--png" => options.png = Some(value),
+            "--load" => options.load = Some(value),
+            _ => return Err(format!("unknown argument: {flag}")),
+        }
+    }
+
+    if options.width == 0 || options.height == 0 {
+        return Err("width and height have to be at least 1".to_string());
+    }
+    Ok(options)
+}
+
+/// Steps the solver until it finds the end, gets stuck or runs out of steps.
+/// Returns how many steps it took.
+fn run(solver: &mut dyn MazeSolver, max_steps: usize) -> usize {
+    let mut steps = 0;
+    while solver.done() == Done::NotFound(false) && steps < max_steps {
+        solver.step();
+        steps += 1;
+    }
+    steps
+}
+
+fn main() -> ExitCode {
+    let options = match parse_args() {
+        Ok(options) => options,
+        Err(message) => {
+            if !message.is_empty() {
+                eprintln!("{message}");
+            }
+            eprintln!("{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
+    let time = Instant::now();
+    let (grid, start, end) = match &options.load {
+        Some(path) => {
+            let loaded = load_maze_file(path).and_then(|document| Ok((document.to_grid()?, document)));
+            match loaded {
+                Ok((grid, document)) => {
+                    let (start, end) = (grid.index(document.start), grid.index(document.goal));
+                    (grid, start, end)
+                }
+                Err(error) => {
+                    eprintln!("couldn't load {path}: {error}");
+                    return ExitCode::FAILURE;
+                }
+            }
+        }
+        None => {
+            let mut grid = Grid::new(options.width, options.height);
+            options.generator.generate(&mut grid, options.seed);
+            braid(&mut grid, options.braid / 100.0, options.seed);
+            let end = grid.index((options.width - 1, options.height - 1));
+            (grid, 0, end)
+        }
+    };
+    let generate_time = time.elapsed();
+    let (width, height) = (grid.width, grid.height);
+
+    let grid = Arc::new(grid);
+    let time = Instant::now();
+    let mut solver = options.solver.build(Arc::clone(&grid), start, end, options.seed, options.heuristic);
+    let steps = run(solver.as_mut(), options.max_steps);
+    let (done, solution, report) = (solver.done(), solver.solution(), solver.report());
+    let solve_time = time.elapsed();
+
+    match &options.load {
+        Some(path) => {
+            println!("size: {width}x{height}, loaded from {path}, solver: {}", options.solver.name());
+            println!("loaded in {generate_time:?}");
+        }
+        None => {
+            println!("size: {width}x{height}, generator: {}, solver: {}, seed: {}", options.generator.name(), options.solver.name(), options.seed);
+            println!("generated in {generate_time:?}");
+        }
+    }
+    println!("{}", analyse(&grid, start, end));
+    println!("solver ran {steps} steps in {solve_time:?}");
+    if !report.is_empty() {
+        println!("{report}");
+    }
+
+    if let Some(path) = &options.ascii {
+        let text = to_ascii(&grid, start, end, &solution);
+        if path == "-" {
+            print!("{text}");
+        } else if let Err(error) = std::fs::write(path, text) {
+            eprintln!("couldn't write {path}: {error}");
+            return ExitCode::FAILURE;
+        }
+    }
+    if let Some(path) = &options.svg {
+        let (path_cells, walkers) = if options.svg_path { (&solution[..], solver.walkers()) } else { (&[][..], &[][..]) };
+        if let Err(error) = save_svg(path, &grid, &options.svg_style, path_cells, walkers) {
+            eprintln!("couldn't write {path}: {error}");
+            return ExitCode::FAILURE;
+        }
+    }
+    if let Some(path) = &options.png {
+        if let Err(error) = save_png(path, &grid, &solution) {
+            eprintln!("couldn't write {path}: {error}");
+            return ExitCode::FAILURE;
+        }
+    }
+
+    if done != Done::Found {
+        println!("no path found");
+        return ExitCode::FAILURE;
+    }
+    let path: Vec<String> = solution.iter()
+        .map(|&node| {
+            let (x, y) = grid.coords(node);
+            format!("({x},{y})")
+        })
+        .collect();
+    println!("path length: {}", solution.len());
+    println!("path: {}", path.join(" "));
+    ExitCode::SUCCESS
+}
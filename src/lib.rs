@@ -0,0 +1,14 @@
+// module names predate the lint
+#![allow(non_snake_case)]
+
+pub mod Nodes;
+pub mod Astar;
+pub mod Generators;
+pub mod Search;
+pub mod Robots;
+pub mod Filling;
+pub mod Analysis;
+pub mod Save;
+pub mod Ascii;
+pub mod Svg;
+pub mod Bitmap;
@@ -1,25 +1,50 @@
-use std::borrow::Borrow;
-use std::rc::Rc;
-use std::{time::Duration, vec};
+// module names predate the lint
+#![allow(non_snake_case)]
+
+use std::sync::Arc;
 
 use nannou::prelude::*;
 use nannou_egui::{self, egui, Egui};
 
-mod Nodes;
-mod Astar;
-use crate::Astar::AStar;
-use crate::Astar::RandomStar;
-use Astar::{Done, MazeSolver};
-use crate::Nodes::Node;
-use std::collections::HashSet;
+mod Drawing;
+use crate::Drawing::DrawNode;
+use second_try::Analysis::{analyse, MazeStats};
+use second_try::Astar::{Done, Heuristic, MazeSolver, SolverKind};
+use second_try::Generators::{braid, GeneratorKind, MazeGenerator};
+use second_try::Nodes::{Grid, Node, Traversal};
+use second_try::Bitmap::save_png;
+use second_try::Save::{load_maze_file, save_json, MazeDocument};
+use second_try::Search::{distance_field, farthest};
+use second_try::Svg::{save_svg, SvgStyle};
 
 // TODO: Make it so it doesnt trace over the same node twice.
 struct Model {
-    nodes: Vec<Vec<Rc<Node>>>,
-    maze_size: usize,
+    grid: Arc<Grid>,
+    width: usize,
+    height: usize,
     stop: bool,
     step_button: bool,
-    random_star: RandomStar,
+    steps_per_frame: usize,
+    solver: Box<dyn MazeSolver>,
+    solver_kind: SolverKind,
+    heuristic: Heuristic,
+    start: usize,
+    end: usize,
+    generator: GeneratorKind,
+    braid: f32, // percent of dead ends to open up
+    animate: bool, // step through generating the maze instead of doing it all at once
+    building: Option<Box<dyn MazeGenerator>>, // the maze is still being made, the solver waits for it
+    seed: u64,
+    seed_text: String, // what's typed in the seed box, only parsed on regenerate
+    // where the maze on screen came from, what gets saved with it. None when a loaded file didn't say
+    maze_seed: Option<u64>,
+    maze_generator: Option<GeneratorKind>,
+    show_distances: bool,
+    distances: Vec<Option<usize>>, // steps from the start to every cell, kept up to date with the start
+    stats: MazeStats,
+    file_path: String,
+    message: String, // how the last save or load went
+    svg_style: SvgStyle,
     egui: Egui,
 
 }
@@ -29,152 +54,185 @@ fn main() {
 }
 
 fn model(app: &App) -> Model {
-    let window_id = app.new_window().view(view).raw_event(raw_window_event).build().unwrap();
+    let window_id = app.new_window().view(view).raw_event(raw_window_event).mouse_pressed(mouse_pressed).build().unwrap();
     let window = app.window(window_id).unwrap();
     let egui = Egui::from_window(&window);
 
-    let mut nodes = Vec::new();
-
-
-    let maze_size = 30;
-
-    for i in 0..maze_size {
-        let mut row = Vec::new();
-        for j in 0..maze_size {
-            row.push(Rc::new(Node::new(vec2((i as f32 - maze_size as f32 / 2.0) as f32, (j as f32 - maze_size as f32/2.0) as f32))));
-        }
-        nodes.push(row);
-    }
+    let (width, height) = (30, 30);
+    let grid = Grid::new(width, height);
 
     
     // let time = std::time::Instant::now();
     
-    generate_maze(maze_size, &mut nodes);
+    let generator = GeneratorKind::RecursiveBacktracker;
+    let seed = random::<u64>();
+    // update carves it a few steps per frame
+    let building = Some(generator.build(seed));
+    let grid = Arc::new(grid);
     // let time2 = std::time::Instant::now();
     
-    // println!("Time to generate maze of size {maze_size} is {:?} ", time2 - time);
+    // println!("Time to generate maze of size {width}x{height} is {:?} ", time2 - time);
     
     // println!("{:?}, {:?}", nodes[0][0], nodes[0][0].connected_nodes);
-    // nodes[1][0].8 {}",nodes[1][0].able_to_move_to(&nodes[9][0]));
-
-    // println!("first node: {:?}", nodes[0][1].connected_nodes);
 
-    // connect random nodes that are beside each other
-    // for i in 0..50 {
-
-    //     let direction_list = [vec2(1.0,0.0), vec2(0.0,1.0), vec2(-1.0,0.0), vec2(0.0,-1.0)];
-    //     let dir = direction_list[random_range(0, 4)];
-    
-    //     // choose random node to connect
-    //     let random_node = vec2(random_range(0, maze_size) as  f32, random_range(0, maze_size) as f32);
-    //     let next_node = vec2(random_node.x + dir.x, random_node.y + dir.y);
-
-    //     if is_outside(random_node, maze_size) || is_outside(next_node, maze_size) {
-    //         continue;
-    //     }
-
-
-    //     if nodes[random_node.x as usize][random_node.y as usize].contains(nodes[next_node.x as usize][next_node.y as usize].borrow()) {
-    //         continue;       
-    //     }
-    //     if nodes[next_node.x as usize][next_node.y as usize].contains(nodes[random_node.x as usize][random_node.y as usize].borrow()) {
-    //         continue;
-    //     }
-
-    //     let random_node = (random_node.x as usize, random_node.y as usize);
-    //     let next_node = (next_node.x as usize, next_node.y as usize);
-
-    //     // convert to tuple of usize
-    
-    //     Node::connect(random_node, next_node, &mut nodes);
-    // }
-
-
-    let a_random = RandomStar::new(Rc::clone(&nodes[0][0]), Rc::clone(&nodes[0][maze_size - 1]));
-    
+    let solver_kind = SolverKind::AStar;
+    let heuristic = Heuristic::Manhattan;
+    let start = grid.index((0, 0));
+    let end = grid.index((width - 1, height - 1));
+    let solver = solver_kind.build(Arc::clone(&grid), start, end, seed, heuristic);
+    let distances = distance_field(&grid, start, Traversal::Undirected);
+    let stats = analyse(&grid, start, end);
 
     Model {
-        nodes,
-        maze_size,
-        random_star: a_random,
+        grid,
+        width,
+        height,
+        solver,
+        solver_kind,
+        heuristic,
+        start,
+        end,
+        generator,
+        braid: 0.0,
+        animate: true,
+        building,
+        seed,
+        seed_text: seed.to_string(),
+        maze_seed: Some(seed),
+        maze_generator: Some(generator),
+        show_distances: false,
+        distances,
+        stats,
+        file_path: "maze.json".to_string(),
+        message: String::new(),
+        svg_style: SvgStyle::default(),
         egui,
         stop: false,
         step_button: false,
+        steps_per_frame: 1,
     }
 }
 
 
-fn update(app: &App, model: &mut Model, _update: Update) {
-    render_egui(&mut model.egui, &mut model.nodes, &mut model.random_star, model.maze_size, &mut model.stop, &mut model.step_button);
+fn update(_app: &App, model: &mut Model, _update: Update) {
+    render_egui(model);
 
     // let mouse_pos = app.mouse.position();
-    // for row in &model.nodes {
-    //     for node in row {
-    //         if (mouse_pos - node.position*Node::DIST).length() <= Node::RAD {
-    //             println!("pos: {}, connections: {:?}", node.position, node.connected_nodes);
-    //         }
+    // for node in model.grid.nodes() {
+    //     if (mouse_pos - node.position*Node::DIST).length() <= Node::RAD {
+    //         println!("pos: {}, connections: {:?}", node.position, node.connected_nodes());
     //     }
     // }
     // if app.elapsed_frames() % 10 == 0 {
-    //     model.random_star.step();
+    //     model.solver.step();
     // }
+    if let Some(generator) = &mut model.building {
+        if model.stop && !model.step_button {
+            return;
+        }
+        // the solver still holds the old grid, so this only copies on the first step
+        let grid = Arc::make_mut(&mut model.grid);
+        let steps = if model.stop { 1 } else { model.steps_per_frame };
+        if (0..steps).all(|_| generator.step(grid)) {
+            return;
+        }
+        // done, hand the finished maze over to the solver
+        model.building = None;
+        braid(grid, model.braid / 100.0, model.seed);
+        refresh(model);
+        return;
+    }
     if model.step_button {
-        model.random_star.step();
+        model.solver.step();
         
     }
     if model.stop {
         return;
     }
-    model.random_star.step();
+    for _ in 0..model.steps_per_frame {
+        model.solver.step();
+    }
 
     
 
 }
 
 fn view(app: &App, model: &Model, frame: Frame) {
-    let draw = app.draw();
+    let draw = app.draw().scale(maze_scale(app, &model.grid));
     draw.background().color(Rgb8::new(30, 203, 225));
 
-    for row in &model.nodes {
-        for node in row {
-            node.draw(&draw, srgba8(0,0,0,0));
-            // if node.is_connected() {
-            //     node.draw_connection(&draw)
-
-            // }
+    let grid = &model.grid;
+    if model.show_distances {
+        // heatmap behind the walls, blue near the start to red at the far end
+        let deepest = farthest(&model.distances).map_or(1, |(_, distance)| distance.max(1));
+        for (node, distance) in model.distances.iter().enumerate() {
+            let Some(distance) = distance else {
+                continue;
+            };
+            let hue = 0.66 * (1.0 - *distance as f32 / deepest as f32);
+            draw.rect()
+                .xy(grid.position(node) * Node::DIST)
+                .w_h(Node::DIST, Node::DIST)
+                .color(hsl(hue, 0.8, 0.6));
         }
     }
-    model.nodes[0][0].draw(&draw, RED.into());
+    for node in grid.nodes() {
+        node.draw(&draw, srgba8(0,0,0,0));
+        // if node.is_connected() {
+        //     node.draw_connection(&draw)
 
-    for node in 1..model.random_star.path.len() {
-        model.random_star.path[node-1].draw(&draw, GREEN.into());
+        // }
     }
-    for walker in model.random_star.walkers.iter() {
+    if let Some(generator) = &model.building {
+        for cell in generator.stack() {
+            grid.node(grid.index(cell)).draw(&draw, ORANGE.into());
+        }
+        if let Some(cell) = generator.current() {
+            grid.node(grid.index(cell)).draw(&draw, RED.into());
+        }
+        draw.to_frame(app, &frame).unwrap();
+        model.egui.draw_to_frame(&frame).unwrap();
+        return;
+    }
+    for node in model.solver.visited() {
+        grid.node(node).draw(&draw, srgba8(0, 0, 0, 60));
+    }
+    for node in model.solver.filled() {
+        grid.node(node).draw(&draw, GREY.into());
+    }
+    for node in model.solver.frontier() {
+        grid.node(node).draw(&draw, YELLOW.into());
+    }
+    for node in model.solver.back_frontier() {
+        grid.node(node).draw(&draw, ORANGE.into());
+    }
+    for walker in model.solver.walkers() {
         match walker.is_done {
-            Done::Found => {
-            for node in 1..walker.path.len() {
-
-                walker.current_node.borrow().draw(&draw, GREEN.into());
-                // draw arrows for connections
-                draw.arrow()
-                    .start(walker.path[node-1].position*Node::DIST)
-                    .end(walker.path[node].position*Node::DIST)
-                    .weight(2.0)
-                    .color(BLUE);
-                }
-                    
-            },
-            Done::NotFound(x) => {
-                if x {
-                    walker.current_node.borrow().draw(&draw, BLUE.into());
-                } else {
-                    walker.current_node.borrow().draw(&draw, RED.into());
-
-                }
-                continue;
-            },
+            Done::Found => grid.node(walker.current_node).draw(&draw, GREEN.into()),
+            Done::NotFound(true) => grid.node(walker.current_node).draw(&draw, BLUE.into()),
+            Done::NotFound(false) => grid.node(walker.current_node).draw(&draw, RED.into()),
         }
     }
+    // start and goal get a ring so they stay visible under the solver's colors
+    for (node, color) in [(model.start, RED), (model.end, GREEN)] {
+        let position = grid.position(node) * Node::DIST;
+        draw.ellipse()
+            .xy(position)
+            .radius(Node::RAD)
+            .no_fill()
+            .stroke(color)
+            .stroke_weight(3.0);
+    }
+
+    let solution = model.solver.solution();
+    for node in 1..solution.len() {
+        // draw arrows for the solution
+        draw.arrow()
+            .start(grid.position(solution[node-1])*Node::DIST)
+            .end(grid.position(solution[node])*Node::DIST)
+            .weight(2.0)
+            .color(BLUE);
+    }
 
 
     draw.to_frame(app, &frame).unwrap();
@@ -182,115 +240,266 @@ fn view(app: &App, model: &Model, frame: Frame) {
 
 }
 
-fn raw_window_event(_app: &App, model: &mut Model, event: &nannou::winit::event::WindowEvent){
-    model.egui.handle_raw_event(event);
-}
-fn render_egui(egui: &mut Egui, nodes: &mut Vec<Vec<Rc<Node>>>, random_star: &mut RandomStar, maze_size: usize, stop: &mut bool, step: &mut bool){
-    let egui = egui;
-    // egui.set_elapsed_time(update.since_start);
-
-    let ctx = egui.begin_frame();
-
-    egui::Window::new("Rum window").show(&ctx, |ui| {
-        // ui.label("res"); // template
-        // ui.add(egui::Slider::new(&mut model.num, 1.0..=40.0));
-        ui.label("reset");
-        let reset_button = ui.button("reset!").clicked();
-        if reset_button {
-            reset(nodes, random_star, maze_size);
-        }
-        let stop_button = ui.button("stop").clicked();
-        if stop_button {
-            *stop = !*stop;
-        }
-        *step = ui.button("step").clicked();
-    });
-}
-fn is_outside(next_pos: Vec2, maze_size: usize) -> bool{
-    next_pos.x > maze_size as f32 - 1.0 || next_pos.y > maze_size as f32 - 1.0 || next_pos.y < 0.0 || next_pos.x < 0.0
+/// Shrinks the drawing so long or big mazes still fit in the window.
+fn maze_scale(app: &App, grid: &Grid) -> f32 {
+    let window = app.window_rect();
+    let maze_width = (grid.width + 1) as f32 * Node::DIST;
+    let maze_height = (grid.height + 1) as f32 * Node::DIST;
+    (window.w() / maze_width).min(window.h() / maze_height).min(1.0)
 }
 
-fn already_explored(explored_nodes_list: &Vec<Vec2>, pos: &Vec2) -> bool{
-    explored_nodes_list.contains(pos)
+/// Left click moves the start, right click moves the goal.
+fn mouse_pressed(app: &App, model: &mut Model, button: MouseButton) {
+    if model.egui.ctx().wants_pointer_input() {
+        return;
+    }
+    let position = app.mouse.position() / maze_scale(app, &model.grid) / Node::DIST;
+    let Some(cell) = model.grid.index_at(position) else {
+        return;
+    };
+    match button {
+        MouseButton::Left => model.start = cell,
+        MouseButton::Right => model.end = cell,
+        _ => return,
+    }
+    refresh(model);
 }
 
-fn generate_maze(maze_size: usize, nodes: &mut Vec<Vec<Rc<Node>>>){
-        // if model.maze_size * model.maze_size <= model.walker.2 {return} // dont crash
-    let mut walker: (Vec2, Vec2, usize) = (vec2(0.0, 0.0), vec2(1.0, 0.0), 1);
-    let mut explored_nodes = vec![vec2(0.0, 0.0)];
-    let mut traced_nodes = HashSet::new();
-
-    let direction_list = [vec2(1.0,0.0), vec2(0.0,1.0), vec2(-1.0,0.0), vec2(0.0,-1.0)];
-
+fn raw_window_event(_app: &App, model: &mut Model, event: &nannou::winit::event::WindowEvent){
+    model.egui.handle_raw_event(event);
+}
+fn render_egui(model: &mut Model){
+    let mut regenerate = false;
+    let mut new_solver = false;
+    let mut pick_farthest = false;
+    let mut save = false;
+    let mut load = false;
+    let mut export_svg = false;
+    let mut export_png = false;
+    {
+        let Model { egui, width, height, solver, solver_kind, heuristic, generator, braid, animate, seed, seed_text, stop, step_button: step, steps_per_frame, show_distances, distances, stats, file_path, message, svg_style, .. } = model;
+        // egui.set_elapsed_time(update.since_start);
+
+        let ctx = egui.begin_frame();
+
+        egui::Window::new("Maze").show(&ctx, |ui| {
+            ui.label("reset");
+            let reset_button = ui.button("reset!").clicked();
+            if reset_button {
+                *seed = random();
+                *seed_text = seed.to_string();
+                regenerate = true;
+            }
+            let stop_button = ui.button(if *stop { "start" } else { "stop" }).clicked();
+            if stop_button {
+                *stop = !*stop;
+            }
+            *step = ui.button("step").clicked();
+            ui.add(egui::Slider::new(steps_per_frame, 1..=200).text("steps per frame"));
+
+            ui.separator();
+            regenerate |= ui.add(egui::Slider::new(width, 2..=200).text("width")).changed();
+            regenerate |= ui.add(egui::Slider::new(height, 2..=200).text("height")).changed();
+            egui::ComboBox::from_label("generator")
+                .selected_text(generator.name())
+                .show_ui(ui, |ui| {
+                    for option in GeneratorKind::ALL {
+                        regenerate |= ui.selectable_value(generator, option, option.name()).clicked();
+                    }
+                });
+            regenerate |= ui.add(egui::Slider::new(braid, 0.0..=100.0).text("braid %")).changed();
+            ui.checkbox(animate, "animate generation");
+
+            ui.label(format!("seed: {seed}"));
+            ui.text_edit_singleline(seed_text);
+            if ui.button("regenerate with seed").clicked() {
+                match seed_text.trim().parse() {
+                    Ok(typed) => {
+                        *seed = typed;
+                        regenerate = true;
+                    }
+                    Err(_) => *seed_text = seed.to_string(),
+                }
+            }
 
-    while explored_nodes.len() <= maze_size * maze_size && walker.2 < maze_size * maze_size {
-        // println!("explored_nodes.len(): {} , maze_size: {}", explored_nodes.len(), maze_size * maze_size);
+            ui.separator();
+            ui.text_edit_singleline(file_path);
+            ui.horizontal(|ui| {
+                save = ui.button("save").clicked();
+                load = ui.button("load").clicked();
+            });
+            ui.collapsing("svg", |ui| {
+                ui.add(egui::Slider::new(&mut svg_style.stroke_width, 0.5..=10.0).text("stroke width"));
+                ui.add(egui::Slider::new(&mut svg_style.margin, 0.0..=100.0).text("margin"));
+                ui.horizontal(|ui| {
+                    ui.label("walls");
+                    ui.text_edit_singleline(&mut svg_style.wall_color);
+                });
+                ui.horizontal(|ui| {
+                    ui.label("path");
+                    ui.text_edit_singleline(&mut svg_style.path_color);
+                });
+                ui.horizontal(|ui| {
+                    ui.label("walkers");
+                    ui.text_edit_singleline(&mut svg_style.walker_color);
+                });
+                ui.horizontal(|ui| {
+                    let mut has_background = svg_style.background.is_some();
+                    if ui.checkbox(&mut has_background, "background").changed() {
+                        svg_style.background = has_background.then(|| "white".to_string());
+                    }
+                    if let Some(background) = &mut svg_style.background {
+                        ui.text_edit_singleline(background);
+                    }
+                });
+                export_svg = ui.button("export svg").clicked();
+            });
+            export_png = ui.button("export png").clicked();
+            if !message.is_empty() {
+                ui.label(message.as_str());
+            }
 
-        let random_dir = random_range(0, 4);
-        let random_dir = direction_list[random_dir];
-        
-        if is_outside(walker.0 + random_dir, maze_size) {
-            continue
-        } 
-        
-        if already_explored(&explored_nodes, &(walker.0 + random_dir)){
-            // walker.2 += 1;
-            while traced_nodes.contains(&(explored_nodes.len() - walker.2)) && walker.2 < maze_size * maze_size {
-                walker.2 += 1;
-                // println!("walker.2: {}", walker.2);
+            ui.separator();
+            ui.checkbox(show_distances, "distance heatmap");
+            if let Some((_, distance)) = farthest(distances) {
+                ui.label(format!("farthest cell: {distance} steps from the start"));
             }
-            let index = explored_nodes.len() - walker.2;
-            walker.1 = walker.0;
-            walker.0 = explored_nodes[index];
-    
-            // println!("index: {}", walker.2);
-            let mut try_again = true;
-            for dir in direction_list{
-                if already_explored(&explored_nodes, &(walker.0 + dir)) {
-                    continue;
-                }
-                if is_outside(walker.0 + dir, maze_size) {
-                    continue;
-                }
-                walker.1 = walker.0;
-                walker.0 += dir;
-                // println!("new pos = {}, from_branch = {}, dir = {dir}", walker.0, walker.0 - dir);
-                try_again = false;
-                break;
-                
+            pick_farthest = ui.button("pick farthest cell as goal").clicked();
+            ui.collapsing("stats", |ui| {
+                ui.label(stats.to_string());
+            });
+
+            ui.separator();
+            egui::ComboBox::from_label("solver")
+                .selected_text(solver_kind.name())
+                .show_ui(ui, |ui| {
+                    for option in SolverKind::ALL {
+                        new_solver |= ui.selectable_value(solver_kind, option, option.name()).clicked();
+                    }
+                });
+            let report = solver.report();
+            if !report.is_empty() {
+                ui.label(report);
             }
-            if try_again {
-    
-                // explored_nodes.remove(explored_nodes.len() - walker.2);
-                // println!("try again");
-                traced_nodes.insert(explored_nodes.len() - walker.2);
-                continue;
+            if *solver_kind == SolverKind::AStar {
+                ui.label("heuristic");
+                for option in Heuristic::ALL {
+                    // only the search restarts, the maze stays the same
+                    new_solver |= ui.radio_value(heuristic, option, option.name()).clicked();
+                }
             }
+        });
+    }
+
+    if save {
+        model.message = if model.building.is_some() {
+            // half carved, it didn't come out of the generator like this
+            "the maze isn't finished yet".to_string()
         } else {
-    
-            // println!("normal");
-            walker.1 = walker.0; // set last pose to this
-            walker.0 += random_dir; // set new pose
+            let document = MazeDocument::from_maze(&model.grid, model.start, model.end, model.maze_seed, model.maze_generator.map(|generator| generator.name()));
+            match save_json(&model.file_path, &document) {
+                Ok(()) => format!("saved {}", model.file_path),
+                Err(error) => format!("couldn't save: {error}"),
+            }
+        };
+    }
+    if export_svg {
+        // next to the json, with the solver's current path and walkers
+        let path = std::path::Path::new(&model.file_path).with_extension("svg");
+        let solution = model.solver.solution();
+        model.message = match save_svg(&path, &model.grid, &model.svg_style, &solution, model.solver.walkers()) {
+            Ok(()) => format!("exported {}", path.display()),
+            Err(error) => format!("couldn't export: {error}"),
+        };
+    }
+    if export_png {
+        let path = std::path::Path::new(&model.file_path).with_extension("png");
+        let solution = model.solver.solution();
+        model.message = match save_png(&path, &model.grid, &solution) {
+            Ok(()) => format!("exported {}", path.display()),
+            Err(error) => format!("couldn't export: {error}"),
+        };
+    }
+    if load {
+        // json, or .txt and .png mazes
+        match load_maze_file(&model.file_path).and_then(|document| Ok((document.to_grid()?, document))) {
+            Ok((grid, document)) => {
+                load_maze(model, grid, &document);
+                model.message = format!("loaded {}", model.file_path);
+            }
+            Err(error) => model.message = error.to_string(),
         }
-    
-        
-        walker.2 = 1; // set tracer index to 1
-    
-        explored_nodes.push(walker.0);
-        // println!("{}, {}", walker.0, walker.1);
-        Node::connect((walker.0.x as usize, walker.0.y as usize), (walker.1.x as usize, walker.1.y as usize), nodes);
+        return;
     }
-    
+    if regenerate {
+        reset(model);
+        return;
+    }
+    if pick_farthest {
+        if let Some((cell, _)) = farthest(&model.distances) {
+            model.end = cell;
+            refresh(model);
+            return;
+        }
+    }
+    if new_solver {
+        model.solver = build_solver(model);
+    }
+}
+
+fn build_solver(model: &Model) -> Box<dyn MazeSolver> {
+    model.solver_kind.build(Arc::clone(&model.grid), model.start, model.end, model.seed, model.heuristic)
+}
+
+fn reset(model: &mut Model){
+    let start = model.grid.coords(model.start);
+    let end = model.grid.coords(model.end);
+
+    let mut new_grid = Grid::new(model.width, model.height);
+    model.maze_seed = Some(model.seed);
+    model.maze_generator = Some(model.generator);
+    model.building = None;
+    if model.animate {
+        // update does the generating a few steps at a time so you can watch
+        model.building = Some(model.generator.build(model.seed));
+    } else {
+        model.generator.generate(&mut new_grid, model.seed);
+        braid(&mut new_grid, model.braid / 100.0, model.seed);
+    }
+    model.grid = Arc::new(new_grid);
+
+    // keep the chosen start and goal as long as they still fit in the maze
+    let (width, height) = (model.width, model.height);
+    let fits = |(x, y): (usize, usize)| x < width && y < height;
+    model.start = model.grid.index(if fits(start) { start } else { (0, 0) });
+    model.end = model.grid.index(if fits(end) { end } else { (width - 1, height - 1) });
+    refresh(model);
 
 }
 
-fn reset(nodes: &mut Vec<Vec<Rc<Node>>>, random_star: &mut RandomStar, maze_size: usize){
-    nodes.iter().for_each(|row| {
-        row.iter().for_each(|node| {
-            node.connected_nodes.borrow_mut().clear();
-        })
-    });
-    *random_star = RandomStar::new(Rc::clone(&nodes[0][0]), Rc::clone(&nodes[maze_size - 1][maze_size - 1]));
-    generate_maze(maze_size, nodes);
+fn load_maze(model: &mut Model, grid: Grid, document: &MazeDocument) {
+    model.building = None;
+    model.width = grid.width;
+    model.height = grid.height;
+    model.start = grid.index(document.start);
+    model.end = grid.index(document.goal);
+    model.grid = Arc::new(grid);
+    model.maze_seed = document.seed;
+    model.maze_generator = document.generator.as_deref().and_then(GeneratorKind::from_name);
+    // a maze saved by hand might not say where it came from, the controls keep what they had
+    if let Some(seed) = model.maze_seed {
+        model.seed = seed;
+        model.seed_text = seed.to_string();
+    }
+    if let Some(generator) = model.maze_generator {
+        model.generator = generator;
+    }
+    refresh(model);
+}
 
-}
\ No newline at end of file
+/// Redoes everything that depends on the maze, the start or the goal.
+fn refresh(model: &mut Model) {
+    model.distances = distance_field(&model.grid, model.start, Traversal::Undirected);
+    model.stats = analyse(&model.grid, model.start, model.end);
+    model.solver = build_solver(model);
+}